@@ -0,0 +1,39 @@
+//! Records the version of the compiler for `cond_version!`.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .expect("failed to run rustc --version");
+    let version = String::from_utf8(output.stdout).expect("rustc --version is not UTF-8");
+    // For example "rustc 1.77.0-nightly (5518eaa94 2024-01-29)".
+    let number = version
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_else(|| panic!("unexpected rustc --version output: {version}"));
+    let (number, channel) = match number.split_once('-') {
+        Some((number, "nightly" | "dev")) => (number, "Nightly"),
+        Some((number, pre)) if pre.starts_with("beta") => (number, "Beta"),
+        Some((number, _)) => (number, "Stable"),
+        None => (number, "Stable"),
+    };
+    let mut parts = number
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let mut next = || parts.next().unwrap_or(0);
+    let (major, minor, patch) = (next(), next(), next());
+
+    let out = Path::new(&env::var_os("OUT_DIR").unwrap()).join("rustc_version.rs");
+    fs::write(
+        out,
+        format!("Rustc {{ version: ({major}, {minor}, {patch}), channel: Channel::{channel} }}\n"),
+    )
+    .unwrap();
+}
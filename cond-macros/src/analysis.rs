@@ -0,0 +1,333 @@
+//! Static reasoning about conditions built from integer comparisons and boolean variables.
+//!
+//! Every variable compared against integer literals only changes the outcome of a condition at
+//! those literals, so checking one point in each interval between them (and the literals
+//! themselves) decides a property for all inputs. Boolean variables contribute both values.
+
+use std::collections::HashMap;
+
+use quote::ToTokens;
+use syn::{BinOp, Expr, Lit, UnOp};
+
+/// A condition the analysis understands.
+#[derive(Debug, Clone)]
+pub enum Formula {
+    Const(bool),
+    Bool(String),
+    Cmp(String, Op, i128),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn from_bin_op(op: &BinOp) -> Option<Op> {
+        Some(match op {
+            BinOp::Lt(_) => Op::Lt,
+            BinOp::Le(_) => Op::Le,
+            BinOp::Gt(_) => Op::Gt,
+            BinOp::Ge(_) => Op::Ge,
+            BinOp::Eq(_) => Op::Eq,
+            BinOp::Ne(_) => Op::Ne,
+            _ => return None,
+        })
+    }
+
+    /// The operator with its operands swapped, so that `5 < x` becomes `x > 5`.
+    fn flip(self) -> Op {
+        match self {
+            Op::Lt => Op::Gt,
+            Op::Le => Op::Ge,
+            Op::Gt => Op::Lt,
+            Op::Ge => Op::Le,
+            Op::Eq | Op::Ne => self,
+        }
+    }
+
+    fn apply(self, lhs: i128, rhs: i128) -> bool {
+        match self {
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+            Op::Eq => lhs == rhs,
+            Op::Ne => lhs != rhs,
+        }
+    }
+}
+
+/// Declared bounds for an integer variable, from `#[cond(domain(x: u8))]`.
+pub struct Domain {
+    pub key: String,
+    pub bounds: (i128, i128),
+}
+
+/// The range of a primitive integer type, clamped to `i128`.
+pub fn integer_bounds(ty: &str) -> Option<(i128, i128)> {
+    Some(match ty {
+        "u8" => (0, u8::MAX.into()),
+        "u16" => (0, u16::MAX.into()),
+        "u32" => (0, u32::MAX.into()),
+        "u64" | "usize" => (0, u64::MAX.into()),
+        "u128" => (0, i128::MAX),
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        "i64" | "isize" => (i64::MIN.into(), i64::MAX.into()),
+        "i128" => (i128::MIN, i128::MAX),
+        _ => return None,
+    })
+}
+
+/// Returns a stable name for expressions that denote a variable: paths and field accesses,
+/// looking through parentheses and dereferences.
+pub fn variable_key(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(path) if path.qself.is_none() => Some(path.to_token_stream().to_string()),
+        Expr::Field(field) => Some(format!(
+            "{}.{}",
+            variable_key(&field.base)?,
+            field.member.to_token_stream()
+        )),
+        Expr::Paren(paren) => variable_key(&paren.expr),
+        Expr::Group(group) => variable_key(&group.expr),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Deref(_)) => variable_key(&unary.expr),
+        _ => None,
+    }
+}
+
+fn integer_literal(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            integer_literal(&unary.expr)?.checked_neg()
+        }
+        Expr::Paren(paren) => integer_literal(&paren.expr),
+        Expr::Group(group) => integer_literal(&group.expr),
+        _ => None,
+    }
+}
+
+/// Translates a condition into a [`Formula`], or `None` if it uses unsupported constructs.
+pub fn lower(expr: &Expr) -> Option<Formula> {
+    match expr {
+        Expr::Paren(paren) => lower(&paren.expr),
+        Expr::Group(group) => lower(&group.expr),
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Bool(b) => Some(Formula::Const(b.value)),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => {
+            Some(Formula::Not(Box::new(lower(&unary.expr)?)))
+        }
+        Expr::Binary(binary) => match binary.op {
+            BinOp::And(_) => Some(Formula::And(
+                Box::new(lower(&binary.left)?),
+                Box::new(lower(&binary.right)?),
+            )),
+            BinOp::Or(_) => Some(Formula::Or(
+                Box::new(lower(&binary.left)?),
+                Box::new(lower(&binary.right)?),
+            )),
+            ref op => {
+                let op = Op::from_bin_op(op)?;
+                if let (Some(key), Some(value)) =
+                    (variable_key(&binary.left), integer_literal(&binary.right))
+                {
+                    Some(Formula::Cmp(key, op, value))
+                } else if let (Some(value), Some(key)) =
+                    (integer_literal(&binary.left), variable_key(&binary.right))
+                {
+                    Some(Formula::Cmp(key, op.flip(), value))
+                } else {
+                    None
+                }
+            }
+        },
+        _ => variable_key(expr).map(Formula::Bool),
+    }
+}
+
+/// A point in the input space: one value per variable, indexed like [`Space`]'s variables.
+pub struct Cell {
+    ints: Vec<i128>,
+    bools: Vec<bool>,
+}
+
+impl Formula {
+    /// Evaluates the formula at a cell of a space built from it.
+    pub fn eval(&self, space: &Space, cell: &Cell) -> bool {
+        match self {
+            Formula::Const(b) => *b,
+            Formula::Bool(key) => cell.bools[space.index.bools[key]],
+            Formula::Cmp(key, op, value) => op.apply(cell.ints[space.index.ints[key]], *value),
+            Formula::Not(inner) => !inner.eval(space, cell),
+            Formula::And(lhs, rhs) => lhs.eval(space, cell) && rhs.eval(space, cell),
+            Formula::Or(lhs, rhs) => lhs.eval(space, cell) || rhs.eval(space, cell),
+        }
+    }
+}
+
+struct Index {
+    ints: HashMap<String, usize>,
+    bools: HashMap<String, usize>,
+}
+
+/// The variables of a set of formulas, with the representative values of each.
+pub struct Space {
+    ints: Vec<(String, Vec<i128>)>,
+    bools: Vec<String>,
+    index: Index,
+}
+
+/// The largest number of cells the analysis is willing to enumerate.
+const MAX_CELLS: u128 = 1 << 16;
+
+impl Space {
+    pub fn new<'a>(
+        formulas: impl IntoIterator<Item = &'a Formula>,
+        domains: &[Domain],
+    ) -> Result<Space, String> {
+        let mut constants: Vec<(String, Vec<i128>)> = Vec::new();
+        let mut bools: Vec<String> = Vec::new();
+        for formula in formulas {
+            collect(formula, &mut constants, &mut bools);
+        }
+        if let Some(key) = bools
+            .iter()
+            .find(|key| constants.iter().any(|(int, _)| int == *key))
+        {
+            return Err(format!(
+                "`{key}` is used both as a boolean and as an integer"
+            ));
+        }
+
+        let mut ints = Vec::with_capacity(constants.len());
+        for (key, values) in constants {
+            let bounds = domains
+                .iter()
+                .find(|domain| domain.key == key)
+                .map(|domain| domain.bounds);
+            let (min, max) = bounds.unwrap_or((i128::MIN, i128::MAX));
+            let mut points: Vec<i128> = values
+                .iter()
+                .flat_map(|&value| [value.checked_sub(1), Some(value), value.checked_add(1)])
+                .flatten()
+                .chain(bounds.into_iter().flat_map(|(min, max)| [min, max]))
+                .filter(|point| (min..=max).contains(point))
+                .collect();
+            points.sort_unstable();
+            points.dedup();
+            ints.push((key, points));
+        }
+
+        let cells = ints
+            .iter()
+            .map(|(_, points)| points.len() as u128)
+            .chain(bools.iter().map(|_| 2))
+            .try_fold(1u128, |total, count| total.checked_mul(count))
+            .unwrap_or(u128::MAX);
+        if cells > MAX_CELLS {
+            return Err("these conditions have too many combinations to analyze".into());
+        }
+
+        let index = Index {
+            ints: ints
+                .iter()
+                .enumerate()
+                .map(|(i, (key, _))| (key.clone(), i))
+                .collect(),
+            bools: bools
+                .iter()
+                .enumerate()
+                .map(|(i, key)| (key.clone(), i))
+                .collect(),
+        };
+        Ok(Space { ints, bools, index })
+    }
+
+    /// Every combination of representative values.
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        let radices: Vec<usize> = self
+            .ints
+            .iter()
+            .map(|(_, points)| points.len())
+            .chain(self.bools.iter().map(|_| 2))
+            .collect();
+        let mut digits = vec![0; radices.len()];
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let cell = Cell {
+                ints: self
+                    .ints
+                    .iter()
+                    .zip(&digits)
+                    .map(|((_, points), &digit)| points[digit])
+                    .collect(),
+                bools: digits[self.ints.len()..].iter().map(|&d| d == 1).collect(),
+            };
+            // Advance the mixed-radix counter.
+            done = true;
+            for (digit, &radix) in digits.iter_mut().zip(&radices) {
+                *digit += 1;
+                if *digit < radix {
+                    done = false;
+                    break;
+                }
+                *digit = 0;
+            }
+            Some(cell)
+        })
+    }
+
+    /// Renders a cell as `x = 0, ready = false`.
+    pub fn describe(&self, cell: &Cell) -> String {
+        let ints = self
+            .ints
+            .iter()
+            .zip(&cell.ints)
+            .map(|((key, _), value)| format!("{key} = {value}"));
+        let bools = self
+            .bools
+            .iter()
+            .zip(&cell.bools)
+            .map(|(key, value)| format!("{key} = {value}"));
+        ints.chain(bools).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn collect(formula: &Formula, ints: &mut Vec<(String, Vec<i128>)>, bools: &mut Vec<String>) {
+    match formula {
+        Formula::Const(_) => {}
+        Formula::Bool(key) => {
+            if !bools.contains(key) {
+                bools.push(key.clone());
+            }
+        }
+        Formula::Cmp(key, _, value) => match ints.iter_mut().find(|(int, _)| int == key) {
+            Some((_, values)) => values.push(*value),
+            None => ints.push((key.clone(), vec![*value])),
+        },
+        Formula::Not(inner) => collect(inner, ints, bools),
+        Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) => {
+            collect(lhs, ints, bools);
+            collect(rhs, ints, bools);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! `#[derive(Classify)]`: a `classify` method from thresholds on a struct's fields.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Fields, Ident, Token, Type};
+
+use crate::table::{self, Arm};
+
+/// Expands to an inherent `classify` method evaluating the arms of every field in order.
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(not_named(&input)),
+        },
+        _ => return Err(not_named(&input)),
+    };
+
+    let mut output = None;
+    let mut default = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("classify"))
+    {
+        attr.parse_args_with(|input: ParseStream| {
+            while !input.is_empty() {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                match key.to_string().as_str() {
+                    "output" => output = Some(input.parse::<Type>()?),
+                    "default" => default = Some(input.parse::<Expr>()?),
+                    _ => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "expected `output = Type` or `default = value`",
+                        ))
+                    }
+                }
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+    let output = output.ok_or_else(|| {
+        syn::Error::new(
+            input.ident.span(),
+            "`#[derive(Classify)]` needs a `#[classify(output = Type)]` attribute on the struct",
+        )
+    })?;
+
+    let mut bound = Vec::new();
+    let mut arms: Vec<Arm> = Vec::new();
+    for field in fields {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("classify"))
+        {
+            let (field_arms, field_default) = attr.parse_args_with(table::parse_arms)?;
+            if let Some(default) = field_default {
+                return Err(syn::Error::new(
+                    default.span(),
+                    "the default goes in `#[classify(default = value)]` on the struct",
+                ));
+            }
+            table::reject_attributes(&field_arms)?;
+            arms.extend(field_arms);
+            let name = field.ident.as_ref().unwrap();
+            if !bound.contains(&name) {
+                bound.push(name);
+            }
+        }
+    }
+
+    let conditions = arms.iter().map(|arm| &arm.condition);
+    let values = arms.iter().map(|arm| &arm.value);
+    let (output, chain) = match default {
+        Some(default) => (
+            quote!(#output),
+            quote!(#(if #conditions { #values } else)* { #default }),
+        ),
+        None => (
+            quote!(::core::option::Option<#output>),
+            quote! {
+                #(if #conditions { ::core::option::Option::Some(#values) } else)* {
+                    ::core::option::Option::None
+                }
+            },
+        ),
+    };
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #type_generics #where_clause {
+            /// Classifies the value by the thresholds on its fields, in order.
+            #[allow(unused_variables)]
+            pub fn classify(&self) -> #output {
+                #(let #bound = self.#bound;)*
+                #chain
+            }
+        }
+    })
+}
+
+fn not_named(input: &DeriveInput) -> syn::Error {
+    syn::Error::new(
+        input.ident.span(),
+        "`#[derive(Classify)]` only supports structs with named fields",
+    )
+}
@@ -0,0 +1,191 @@
+//! `cond_enum!`: unifying arm types through a generated enum.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Ident, Path, Token};
+
+use crate::table::{self, Arm};
+
+/// A parsed `cond_enum!` invocation.
+pub struct CondEnum {
+    traits: Vec<Trait>,
+    arms: Vec<Arm>,
+    default: Expr,
+}
+
+/// The traits the generated enum can delegate.
+#[derive(Clone, Copy, PartialEq)]
+enum Trait {
+    Iterator,
+    DoubleEndedIterator,
+    ExactSizeIterator,
+    Display,
+    Debug,
+    Future,
+}
+
+impl Trait {
+    fn from_path(path: &Path) -> syn::Result<Trait> {
+        let name = path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string());
+        Ok(match name.as_deref() {
+            Some("Iterator") => Trait::Iterator,
+            Some("DoubleEndedIterator") => Trait::DoubleEndedIterator,
+            Some("ExactSizeIterator") => Trait::ExactSizeIterator,
+            Some("Display") => Trait::Display,
+            Some("Debug") => Trait::Debug,
+            Some("Future") => Trait::Future,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "`cond_enum!` can only delegate `Iterator`, `DoubleEndedIterator`, \
+                     `ExactSizeIterator`, `Display`, `Debug` and `Future`",
+                ))
+            }
+        })
+    }
+
+    fn is_iterator(self) -> bool {
+        matches!(
+            self,
+            Trait::Iterator | Trait::DoubleEndedIterator | Trait::ExactSizeIterator
+        )
+    }
+}
+
+impl Parse for CondEnum {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![impl]>()?;
+        let paths = Punctuated::<Path, Token![+]>::parse_separated_nonempty(input)?;
+        input.parse::<Token![;]>()?;
+        let mut traits = Vec::new();
+        for path in &paths {
+            let t = Trait::from_path(path)?;
+            if !traits.contains(&t) {
+                traits.push(t);
+            }
+        }
+
+        let (arms, default) = table::parse_arms(input)?;
+        table::reject_attributes(&arms)?;
+        let default = default.ok_or_else(|| input.error("`cond_enum!` requires a default arm"))?;
+        Ok(CondEnum {
+            traits,
+            arms,
+            default,
+        })
+    }
+}
+
+pub fn expand(input: CondEnum) -> TokenStream {
+    let name = Ident::new("CondEnum", Span::mixed_site());
+    let count = input.arms.len() + 1;
+    let params: Vec<Ident> = (0..count).map(|i| format_ident!("T{}", i)).collect();
+    let variants: Vec<Ident> = (0..count).map(|i| format_ident!("Arm{}", i)).collect();
+    let first = &params[0];
+    let rest = &params[1..];
+
+    let mut impls = TokenStream::new();
+    let mut traits = input.traits.clone();
+    // The other iterator traits need `Iterator` itself.
+    if traits.iter().any(|t| t.is_iterator()) && !traits.contains(&Trait::Iterator) {
+        traits.push(Trait::Iterator);
+    }
+    for t in traits {
+        impls.extend(match t {
+            Trait::Iterator => quote! {
+                impl<#(#params),*> ::core::iter::Iterator for #name<#(#params),*>
+                where
+                    #first: ::core::iter::Iterator,
+                    #(#rest: ::core::iter::Iterator<Item = <#first as ::core::iter::Iterator>::Item>,)*
+                {
+                    type Item = <#first as ::core::iter::Iterator>::Item;
+
+                    fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                        match self { #(Self::#variants(inner) => inner.next()),* }
+                    }
+
+                    fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+                        match self { #(Self::#variants(inner) => inner.size_hint()),* }
+                    }
+                }
+            },
+            Trait::DoubleEndedIterator => quote! {
+                impl<#(#params),*> ::core::iter::DoubleEndedIterator for #name<#(#params),*>
+                where
+                    #first: ::core::iter::DoubleEndedIterator,
+                    #(#rest: ::core::iter::DoubleEndedIterator<Item = <#first as ::core::iter::Iterator>::Item>,)*
+                {
+                    fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                        match self { #(Self::#variants(inner) => inner.next_back()),* }
+                    }
+                }
+            },
+            Trait::ExactSizeIterator => quote! {
+                impl<#(#params),*> ::core::iter::ExactSizeIterator for #name<#(#params),*>
+                where
+                    #first: ::core::iter::ExactSizeIterator,
+                    #(#rest: ::core::iter::ExactSizeIterator<Item = <#first as ::core::iter::Iterator>::Item>,)*
+                {
+                }
+            },
+            Trait::Display => quote! {
+                impl<#(#params: ::core::fmt::Display),*> ::core::fmt::Display for #name<#(#params),*> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self { #(Self::#variants(inner) => ::core::fmt::Display::fmt(inner, f)),* }
+                    }
+                }
+            },
+            Trait::Debug => quote! {
+                impl<#(#params: ::core::fmt::Debug),*> ::core::fmt::Debug for #name<#(#params),*> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self { #(Self::#variants(inner) => ::core::fmt::Debug::fmt(inner, f)),* }
+                    }
+                }
+            },
+            Trait::Future => quote! {
+                impl<#(#params),*> ::core::future::Future for #name<#(#params),*>
+                where
+                    #first: ::core::future::Future,
+                    #(#rest: ::core::future::Future<Output = <#first as ::core::future::Future>::Output>,)*
+                {
+                    type Output = <#first as ::core::future::Future>::Output;
+
+                    fn poll(
+                        self: ::core::pin::Pin<&mut Self>,
+                        cx: &mut ::core::task::Context<'_>,
+                    ) -> ::core::task::Poll<Self::Output> {
+                        // SAFETY: the inner future is never moved out of the enum, and the enum
+                        // never changes variant, so pinning the enum pins the inner future.
+                        unsafe {
+                            match self.get_unchecked_mut() {
+                                #(Self::#variants(inner) => ::core::pin::Pin::new_unchecked(inner).poll(cx)),*
+                            }
+                        }
+                    }
+                }
+            },
+        });
+    }
+
+    let conditions = input.arms.iter().map(|arm| &arm.condition);
+    let values = input.arms.iter().map(|arm| &arm.value);
+    let arm_variants = &variants[..count - 1];
+    let default_variant = &variants[count - 1];
+    let default = &input.default;
+    quote! {{
+        enum #name<#(#params),*> {
+            #(#variants(#params)),*
+        }
+
+        #impls
+
+        #(if #conditions { #name::#arm_variants(#values) } else)* {
+            #name::#default_variant(#default)
+        }
+    }}
+}
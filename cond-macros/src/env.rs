@@ -0,0 +1,127 @@
+//! `cond_env!`: arms selected by build-time environment variables.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{BinOp, Expr, ExprLit, Lit, LitStr, UnOp};
+
+use crate::table::{self, Arm};
+
+/// A parsed `cond_env!` invocation.
+pub struct CondEnv {
+    arms: Vec<Arm>,
+    default: Option<Expr>,
+}
+
+impl Parse for CondEnv {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let (arms, default) = table::parse_arms(input)?;
+        table::reject_attributes(&arms)?;
+        Ok(CondEnv { arms, default })
+    }
+}
+
+/// Evaluates the conditions in order and expands to the value of the first arm that holds, or
+/// the default. Every variable read is also read with `option_env!`, so that Cargo rebuilds the
+/// crate when one changes.
+pub fn expand(table: CondEnv) -> syn::Result<TokenStream> {
+    let mut reader = Reader::default();
+    let mut chosen = None;
+    for arm in &table.arms {
+        // Keep evaluating after a match, so every condition is checked for errors.
+        if reader.eval(&arm.condition)? && chosen.is_none() {
+            chosen = Some(&arm.value);
+        }
+    }
+    // With nothing selected, the block has no tail and evaluates to `()`.
+    let value = chosen.or(table.default.as_ref());
+    let tracked = reader.read.iter().map(|name| {
+        quote!(
+            const _: ::core::option::Option<&str> = ::core::option_env!(#name);
+        )
+    });
+    Ok(quote!({ #(#tracked)* #value }))
+}
+
+#[derive(Default)]
+struct Reader {
+    read: Vec<LitStr>,
+}
+
+impl Reader {
+    fn eval(&mut self, condition: &Expr) -> syn::Result<bool> {
+        match condition {
+            Expr::Lit(lit) => match &lit.lit {
+                Lit::Bool(b) => Ok(b.value),
+                _ => Err(unsupported(condition)),
+            },
+            Expr::Paren(paren) => self.eval(&paren.expr),
+            Expr::Group(group) => self.eval(&group.expr),
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => Ok(!self.eval(&unary.expr)?),
+            Expr::Binary(binary) => match binary.op {
+                BinOp::And(_) => Ok(self.eval(&binary.left)? & self.eval(&binary.right)?),
+                BinOp::Or(_) => Ok(self.eval(&binary.left)? | self.eval(&binary.right)?),
+                BinOp::Eq(_) | BinOp::Ne(_) => {
+                    let (var, value) = match (self.var(&binary.left), self.var(&binary.right)) {
+                        (Some(var), None) => (var?, string(&binary.right)?),
+                        (None, Some(var)) => (var?, string(&binary.left)?),
+                        _ => return Err(unsupported(condition)),
+                    };
+                    let equal = var.as_deref() == Some(value.as_str());
+                    Ok(equal == matches!(binary.op, BinOp::Eq(_)))
+                }
+                _ => Err(unsupported(condition)),
+            },
+            _ => match self.var(condition) {
+                Some(var) => Ok(var?.is_some()),
+                None => Err(unsupported(condition)),
+            },
+        }
+    }
+
+    /// Reads the variable if `expr` is `env("NAME")`, or returns `None` if it is something else.
+    fn var(&mut self, expr: &Expr) -> Option<syn::Result<Option<String>>> {
+        let Expr::Call(call) = expr else {
+            return None;
+        };
+        let Expr::Path(func) = &*call.func else {
+            return None;
+        };
+        if !func.path.is_ident("env") {
+            return None;
+        }
+        let name = match call.args.iter().collect::<Vec<_>>()[..] {
+            [Expr::Lit(ExprLit {
+                lit: Lit::Str(name),
+                ..
+            })] => name,
+            _ => {
+                return Some(Err(syn::Error::new(
+                    call.span(),
+                    "`env` takes the name of a variable as a string literal",
+                )))
+            }
+        };
+        self.read.push(name.clone());
+        Some(Ok(std::env::var(name.value()).ok()))
+    }
+}
+
+fn string(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => Ok(s.value()),
+            _ => Err(syn::Error::new(lit.span(), "expected a string literal")),
+        },
+        _ => Err(syn::Error::new(expr.span(), "expected a string literal")),
+    }
+}
+
+fn unsupported(condition: &Expr) -> syn::Error {
+    syn::Error::new(
+        condition.span(),
+        "`cond_env!` conditions can only use `env(\"NAME\")`, comparisons of it with string \
+         literals, `true`, `false`, `!`, `&&` and `||`",
+    )
+}
@@ -0,0 +1,63 @@
+//! Procedural macros for the [`cond`](https://docs.rs/cond) crate.
+//!
+//! Enable the `macros` feature of `cond` and use the macros through it rather than depending on
+//! this crate directly.
+
+use proc_macro::TokenStream;
+use syn::parse_macro_input;
+
+mod analysis;
+mod classify;
+mod enums;
+mod env;
+mod purity;
+mod sql;
+mod table;
+mod version;
+
+/// `cond!` with compile-time analysis of its conditions.
+#[proc_macro]
+pub fn cond(input: TokenStream) -> TokenStream {
+    let table = parse_macro_input!(input as table::Table);
+    table::expand(table)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives a `classify` method from thresholds on a struct's fields.
+#[proc_macro_derive(Classify, attributes(classify))]
+pub fn derive_classify(input: TokenStream) -> TokenStream {
+    classify::expand(parse_macro_input!(input as syn::DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Unifies arms of different types through a generated enum.
+#[proc_macro]
+pub fn cond_enum(input: TokenStream) -> TokenStream {
+    enums::expand(parse_macro_input!(input as enums::CondEnum)).into()
+}
+
+/// Selects an arm by build-time environment variables.
+#[proc_macro]
+pub fn cond_env(input: TokenStream) -> TokenStream {
+    let table = parse_macro_input!(input as env::CondEnv);
+    env::expand(table)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Defines a function and the equivalent SQL `CASE` expression from one table.
+#[proc_macro]
+pub fn cond_sql(input: TokenStream) -> TokenStream {
+    let table = parse_macro_input!(input as sql::CondSql);
+    sql::expand(table)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Selects an arm by the version of the compiler.
+#[proc_macro]
+pub fn cond_version(input: TokenStream) -> TokenStream {
+    version::expand(parse_macro_input!(input as version::CondVersion)).into()
+}
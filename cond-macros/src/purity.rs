@@ -0,0 +1,114 @@
+//! Detection of side effects in conditions, for `#[cond(pure)]`.
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr};
+
+/// Methods that usually mutate their receiver or have other effects, by name. Conditions are
+/// checked without types, so this can only catch the usual suspects.
+const EFFECTFUL_METHODS: &[&str] = &[
+    "append",
+    "clear",
+    "dedup",
+    "drain",
+    "entry",
+    "extend",
+    "get_or_insert",
+    "get_or_insert_with",
+    "insert",
+    "next",
+    "next_back",
+    "pop",
+    "pop_back",
+    "pop_front",
+    "push",
+    "push_back",
+    "push_front",
+    "remove",
+    "replace",
+    "retain",
+    "reverse",
+    "send",
+    "set",
+    "sort",
+    "sort_by",
+    "sort_by_key",
+    "sort_unstable",
+    "store",
+    "swap",
+    "swap_remove",
+    "take",
+    "truncate",
+];
+
+/// Macros that write output or panic.
+const EFFECTFUL_MACROS: &[&str] = &[
+    "dbg", "eprint", "eprintln", "panic", "print", "println", "todo", "write", "writeln",
+];
+
+/// Returns the first obvious side effect in `condition`, as its location and a description.
+pub fn side_effect(condition: &Expr) -> Option<(Span, String)> {
+    let mut finder = Finder(None);
+    finder.visit_expr(condition);
+    finder.0
+}
+
+struct Finder(Option<(Span, String)>);
+
+impl Finder {
+    fn found(&mut self, span: Span, description: String) {
+        self.0.get_or_insert((span, description));
+    }
+}
+
+impl<'ast> Visit<'ast> for Finder {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if self.0.is_some() {
+            return;
+        }
+        match expr {
+            Expr::Assign(assign) => self.found(assign.span(), "an assignment".to_owned()),
+            Expr::Binary(binary) if is_compound_assignment(&binary.op) => {
+                self.found(binary.span(), "an assignment".to_owned())
+            }
+            Expr::Reference(reference) if reference.mutability.is_some() => {
+                self.found(reference.span(), "a mutable borrow".to_owned())
+            }
+            Expr::MethodCall(call) if EFFECTFUL_METHODS.contains(&&*call.method.to_string()) => {
+                self.found(
+                    call.method.span(),
+                    format!(
+                        "a call to `{}`, which usually mutates its receiver",
+                        call.method
+                    ),
+                )
+            }
+            Expr::Await(await_expr) => self.found(await_expr.span(), "an `.await`".to_owned()),
+            Expr::Macro(mac) => {
+                let name = mac.mac.path.segments.last().map(|s| s.ident.to_string());
+                if let Some(name) = name.filter(|name| EFFECTFUL_MACROS.contains(&name.as_str())) {
+                    self.found(mac.span(), format!("a `{}!` invocation", name));
+                }
+            }
+            _ => {}
+        }
+        visit::visit_expr(self, expr);
+    }
+}
+
+fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
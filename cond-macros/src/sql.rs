@@ -0,0 +1,236 @@
+//! `cond_sql!`: a function and the equivalent SQL `CASE` expression from one table.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    braced, parenthesized, Attribute, BinOp, Expr, ExprLit, Ident, Lit, Meta, Token, Type, UnOp,
+    Visibility,
+};
+
+use crate::table::{self, Arm};
+
+/// A parsed `cond_sql!` invocation.
+pub struct CondSql {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    params: Vec<Param>,
+    output: Type,
+    arms: Vec<Arm>,
+    default: Expr,
+}
+
+/// A parameter of the function, which the SQL reads from a column.
+struct Param {
+    column: String,
+    name: Ident,
+    ty: Type,
+}
+
+impl Parse for CondSql {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![fn]>()?;
+        let name = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let params = Punctuated::<Param, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .collect();
+        input.parse::<Token![->]>()?;
+        let output = input.parse()?;
+        let content;
+        let brace = braced!(content in input);
+        let (arms, default) = table::parse_arms(&content)?;
+        table::reject_attributes(&arms)?;
+        let default = default.ok_or_else(|| {
+            syn::Error::new(
+                brace.span.close(),
+                "`cond_sql!` tables need a default arm, which becomes the `ELSE` of the `CASE`",
+            )
+        })?;
+        Ok(CondSql {
+            attrs,
+            vis,
+            name,
+            params,
+            output,
+            arms,
+            default,
+        })
+    }
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name: Ident = input.parse()?;
+        let mut column = name.unraw().to_string();
+        for attr in attrs {
+            match &attr.meta {
+                Meta::NameValue(meta) if meta.path.is_ident("column") => match &meta.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(name),
+                        ..
+                    }) => column = name.value(),
+                    value => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "expected the name of the column as a string literal",
+                        ))
+                    }
+                },
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "the only attribute supported on parameters is `#[column = \"name\"]`",
+                    ))
+                }
+            }
+        }
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Param { column, name, ty })
+    }
+}
+
+/// Expands to the function, evaluating the arms as a chain of `if`s, and a constant with the SQL
+/// `CASE` expression.
+pub fn expand(table: CondSql) -> syn::Result<TokenStream> {
+    let writer = Writer {
+        params: &table.params,
+    };
+    let mut sql = String::from("CASE");
+    for arm in &table.arms {
+        sql += &format!(
+            " WHEN {} THEN {}",
+            writer.condition(&arm.condition)?,
+            writer.operand(&arm.value)?
+        );
+    }
+    sql += &format!(" ELSE {} END", writer.operand(&table.default)?);
+
+    let CondSql {
+        attrs,
+        vis,
+        name,
+        params,
+        output,
+        arms,
+        default,
+    } = &table;
+    let names = params.iter().map(|param| &param.name);
+    let types = params.iter().map(|param| &param.ty);
+    let conditions = arms.iter().map(|arm| &arm.condition);
+    let values = arms.iter().map(|arm| &arm.value);
+    let constant = format_ident!("{}_SQL", name.unraw().to_string().to_uppercase());
+    let doc = format!(
+        "The SQL form of [`{}`], as a `CASE` expression.",
+        name.unraw()
+    );
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #name(#(#names: #types),*) -> #output {
+            #(if #conditions { #values } else)* { #default }
+        }
+
+        #[doc = #doc]
+        #vis const #constant: &str = #sql;
+    })
+}
+
+struct Writer<'a> {
+    params: &'a [Param],
+}
+
+impl Writer<'_> {
+    fn condition(&self, expr: &Expr) -> syn::Result<String> {
+        match expr {
+            Expr::Paren(paren) => Ok(format!("({})", self.condition(&paren.expr)?)),
+            Expr::Group(group) => self.condition(&group.expr),
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => {
+                Ok(format!("NOT {}", self.condition(&unary.expr)?))
+            }
+            Expr::Binary(binary) => {
+                let op = match binary.op {
+                    BinOp::And(_) => "AND",
+                    BinOp::Or(_) => "OR",
+                    BinOp::Eq(_) => "=",
+                    BinOp::Ne(_) => "<>",
+                    BinOp::Lt(_) => "<",
+                    BinOp::Le(_) => "<=",
+                    BinOp::Gt(_) => ">",
+                    BinOp::Ge(_) => ">=",
+                    _ => return Err(unsupported(expr)),
+                };
+                let (left, right) = if matches!(binary.op, BinOp::And(_) | BinOp::Or(_)) {
+                    (
+                        self.condition(&binary.left)?,
+                        self.condition(&binary.right)?,
+                    )
+                } else {
+                    (self.operand(&binary.left)?, self.operand(&binary.right)?)
+                };
+                Ok(format!("{} {} {}", left, op, right))
+            }
+            Expr::Lit(_) | Expr::Path(_) => self.operand(expr),
+            _ => Err(unsupported(expr)),
+        }
+    }
+
+    /// Writes a column or a literal.
+    fn operand(&self, expr: &Expr) -> syn::Result<String> {
+        match expr {
+            Expr::Paren(paren) => self.operand(&paren.expr),
+            Expr::Group(group) => self.operand(&group.expr),
+            Expr::Path(path) => {
+                let param = path
+                    .path
+                    .get_ident()
+                    .and_then(|ident| self.params.iter().find(|param| param.name == *ident));
+                match param {
+                    Some(param) => Ok(param.column.clone()),
+                    None => Err(syn::Error::new(
+                        expr.span(),
+                        "`cond_sql!` can only refer to the function's parameters",
+                    )),
+                }
+            }
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => match &*unary.expr {
+                Expr::Lit(lit) if matches!(lit.lit, Lit::Int(_) | Lit::Float(_)) => {
+                    Ok(format!("-{}", literal(&lit.lit)?))
+                }
+                _ => Err(unsupported(expr)),
+            },
+            Expr::Lit(lit) => literal(&lit.lit),
+            _ => Err(unsupported(expr)),
+        }
+    }
+}
+
+fn literal(lit: &Lit) -> syn::Result<String> {
+    match lit {
+        Lit::Str(s) => Ok(format!("'{}'", s.value().replace('\'', "''"))),
+        Lit::Char(c) => Ok(format!("'{}'", c.value().to_string().replace('\'', "''"))),
+        Lit::Int(int) => Ok(int.base10_digits().to_owned()),
+        Lit::Float(float) => Ok(float.base10_digits().to_owned()),
+        Lit::Bool(b) => Ok(if b.value { "TRUE" } else { "FALSE" }.to_owned()),
+        _ => Err(syn::Error::new(
+            lit.span(),
+            "`cond_sql!` supports string, character, integer, float and bool literals",
+        )),
+    }
+}
+
+fn unsupported(expr: &Expr) -> syn::Error {
+    syn::Error::new(
+        expr.span(),
+        "`cond_sql!` conditions can only use parameters, literals, comparisons, `!`, `&&` and \
+         `||`, and values can only be parameters or literals",
+    )
+}
@@ -0,0 +1,416 @@
+//! Parsing and expansion of `cond!` tables.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Ident, Token};
+
+use crate::analysis::{self, Domain, Space};
+use crate::purity;
+
+/// A parsed `cond!` invocation.
+pub struct Table {
+    options: Options,
+    arms: Vec<Arm>,
+    default: Option<Expr>,
+}
+
+/// One `condition => value` arm, with the attributes written before it.
+pub struct Arm {
+    pub attrs: Vec<Attribute>,
+    pub condition: Expr,
+    pub value: Expr,
+}
+
+/// The table-wide `#[cond(...)]` options.
+#[derive(Default)]
+struct Options {
+    exhaustive: Option<Span>,
+    disjoint: Option<Span>,
+    pure: bool,
+    domains: Vec<Domain>,
+}
+
+impl Parse for Table {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        // Attributes before the first arm are options if they are `#[cond(...)]`, and belong to
+        // the first arm otherwise.
+        let mut first_attrs = Vec::new();
+        for attr in input.call(Attribute::parse_outer)? {
+            if attr.path().is_ident("cond") {
+                options.parse_attribute(&attr)?;
+            } else {
+                first_attrs.push(attr);
+            }
+        }
+        // `cond! { , _ => value }` is accepted by the declarative macro, so accept it here too.
+        if first_attrs.is_empty() && input.peek(Token![,]) && input.peek2(Token![_]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        let (mut arms, default) = parse_arms(input)?;
+        match arms.first_mut() {
+            Some(first) => first.attrs = first_attrs,
+            None => {
+                if let Some(attr) = first_attrs.first() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "attributes are not supported on the default arm",
+                    ));
+                }
+            }
+        }
+        sort_by_priority(&mut arms)?;
+        Ok(Table {
+            options,
+            arms,
+            default,
+        })
+    }
+}
+
+/// Parses `condition => value` arms separated by commas, optionally ending with a `_ => default`
+/// arm. Attributes before an arm are kept in [`Arm::attrs`].
+pub fn parse_arms(input: ParseStream) -> syn::Result<(Vec<Arm>, Option<Expr>)> {
+    let mut arms = Vec::new();
+    let mut default = None;
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        if input.peek(Token![_]) {
+            if let Some(attr) = attrs.first() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "attributes are not supported on the default arm",
+                ));
+            }
+            input.parse::<Token![_]>()?;
+            input.parse::<Token![=>]>()?;
+            default = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+            if !input.is_empty() {
+                return Err(input.error("the default arm must come last"));
+            }
+            break;
+        }
+        let condition = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        arms.push(Arm {
+            attrs,
+            condition,
+            value,
+        });
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+
+    Ok((arms, default))
+}
+
+/// Rejects arm attributes, for macros that do not support any.
+pub fn reject_attributes(arms: &[Arm]) -> syn::Result<()> {
+    match arms.iter().flat_map(|arm| &arm.attrs).next() {
+        Some(attr) => Err(syn::Error::new_spanned(
+            attr,
+            "attributes are not supported on the arms of this macro",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Handles `#[priority(n)]` arm attributes: if any arm has one, every arm must, no two may share a
+/// priority, and the arms are sorted so that higher priorities are checked first.
+fn sort_by_priority(arms: &mut Vec<Arm>) -> syn::Result<()> {
+    let mut priorities = Vec::with_capacity(arms.len());
+    for arm in arms.iter() {
+        let mut priority = None;
+        for attr in &arm.attrs {
+            if !attr.path().is_ident("priority") {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "only `#[priority(...)]` attributes are supported on arms",
+                ));
+            }
+            if priority.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "duplicate `#[priority(...)]`",
+                ));
+            }
+            priority = Some(attr.parse_args_with(parse_priority)?);
+        }
+        priorities.push(priority);
+    }
+    if priorities.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    if let Some(index) = priorities.iter().position(Option::is_none) {
+        return Err(syn::Error::new_spanned(
+            &arms[index].condition,
+            "this arm needs a `#[priority(...)]`, because other arms of the table have one",
+        ));
+    }
+    let mut keyed: Vec<(i64, Arm)> = priorities
+        .into_iter()
+        .flatten()
+        .zip(arms.drain(..))
+        .collect();
+    // The sort is stable, so equal priorities keep their order for the error below.
+    keyed.sort_by_key(|(priority, _)| core::cmp::Reverse(*priority));
+    for pair in keyed.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            return Err(syn::Error::new_spanned(
+                &pair[1].1.attrs[0],
+                format!("another arm already has priority {}", pair[1].0),
+            ));
+        }
+    }
+    arms.extend(keyed.into_iter().map(|(_, arm)| arm));
+    Ok(())
+}
+
+/// Parses the argument of `#[priority(...)]`: an integer literal, optionally negated.
+fn parse_priority(input: ParseStream) -> syn::Result<i64> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value = input.parse::<syn::LitInt>()?.base10_parse::<i64>()?;
+    Ok(if negative { -value } else { value })
+}
+
+impl Options {
+    fn parse_attribute(&mut self, attr: &Attribute) -> syn::Result<()> {
+        if !attr.path().is_ident("cond") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only `#[cond(...)]` attributes are supported here",
+            ));
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("exhaustive") {
+                self.exhaustive = Some(meta.path.span());
+            } else if meta.path.is_ident("disjoint") {
+                self.disjoint = Some(meta.path.span());
+            } else if meta.path.is_ident("pure") {
+                self.pure = true;
+            } else if meta.path.is_ident("domain") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                while !content.is_empty() {
+                    self.domains.push(content.call(parse_domain)?);
+                    if content.is_empty() {
+                        break;
+                    }
+                    content.parse::<Token![,]>()?;
+                }
+            } else {
+                return Err(
+                    meta.error("expected `exhaustive`, `disjoint`, `pure` or `domain(...)`")
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Parses `variable: integer_type`.
+fn parse_domain(input: ParseStream) -> syn::Result<Domain> {
+    let variable: Expr = input.parse()?;
+    let key = analysis::variable_key(&variable)
+        .ok_or_else(|| syn::Error::new_spanned(&variable, "expected a variable"))?;
+    input.parse::<Token![:]>()?;
+    let ty: Ident = input.parse()?;
+    let bounds = analysis::integer_bounds(&ty.to_string())
+        .ok_or_else(|| syn::Error::new_spanned(&ty, "expected a primitive integer type"))?;
+    Ok(Domain { key, bounds })
+}
+
+pub fn expand(table: Table) -> syn::Result<TokenStream> {
+    let Analysis {
+        arm_warnings,
+        default_warning,
+    } = analyze(&table)?;
+
+    // The generated tokens are located at the arms they come from, so that type errors point at
+    // the offending condition or value rather than at the whole invocation. They are still marked
+    // as coming from the macro, so lints about the shape of the expansion stay quiet.
+    let at = |span: Span| Span::call_site().located_at(span);
+    let mut chain = match &table.default {
+        Some(default) => quote_spanned!(at(default.span())=> { #default_warning #default }),
+        None if table.options.exhaustive.is_some() => quote!({ ::core::unreachable!() }),
+        None => quote!({}),
+    };
+    for (arm, warning) in table.arms.iter().zip(arm_warnings).rev() {
+        let Arm {
+            condition, value, ..
+        } = arm;
+        let body = quote_spanned!(at(value.span())=> { #warning #value });
+        chain = quote_spanned!(at(condition.span())=> if #condition #body else #chain);
+    }
+    Ok(chain)
+}
+
+/// The warnings to emit inside each arm, and inside the default arm.
+struct Analysis {
+    arm_warnings: Vec<TokenStream>,
+    default_warning: TokenStream,
+}
+
+/// Runs the analyses requested by the table options, and looks for arms that can never be
+/// selected.
+///
+/// Conditions the analysis does not understand are errors when an option was requested, and are
+/// otherwise treated as never holding, which can only hide warnings, never cause spurious ones.
+fn analyze(table: &Table) -> syn::Result<Analysis> {
+    let options = &table.options;
+    let mut analysis = Analysis {
+        arm_warnings: vec![TokenStream::new(); table.arms.len()],
+        default_warning: TokenStream::new(),
+    };
+    if let (Some(_), Some(default)) = (options.exhaustive, &table.default) {
+        return Err(syn::Error::new_spanned(
+            default,
+            "an exhaustive table does not need a default arm; remove the `_` arm",
+        ));
+    }
+
+    if options.pure {
+        for (index, arm) in table.arms.iter().enumerate() {
+            if let Some((span, effect)) = purity::side_effect(&arm.condition) {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "the condition of {} has {}, but the table is `pure`; it only runs when \
+                         no earlier condition holds",
+                        describe_arm(table, index),
+                        effect,
+                    ),
+                ));
+            }
+        }
+    }
+
+    let requested = options.exhaustive.is_some() || options.disjoint.is_some();
+    let mut formulas = Vec::with_capacity(table.arms.len());
+    for arm in &table.arms {
+        let formula = analysis::lower(&arm.condition);
+        if formula.is_none() && requested {
+            return Err(syn::Error::new_spanned(
+                &arm.condition,
+                "this condition cannot be analyzed; only comparisons of variables with integer \
+                 literals, boolean variables, `!`, `&&` and `||` are supported",
+            ));
+        }
+        formulas.push(formula);
+    }
+    let space = match Space::new(formulas.iter().flatten(), &options.domains) {
+        Ok(space) => space,
+        Err(message) if requested => return Err(syn::Error::new(Span::call_site(), message)),
+        Err(_) => return Ok(analysis),
+    };
+
+    let arms = table.arms.len();
+    // `reachable[j]`: some input selects arm `j`.
+    let mut reachable = vec![false; arms];
+    // `overlapping[j][i]`: some input satisfies both arm `i < j` and arm `j`.
+    let mut overlapping = vec![vec![false; arms]; arms];
+    // `implying[j][i]`: every input satisfying arm `j` also satisfies arm `i < j`.
+    let mut implying = vec![vec![true; arms]; arms];
+    let mut satisfiable = vec![false; arms];
+    let mut default_reachable = false;
+
+    for cell in space.cells() {
+        let hits: Vec<bool> = formulas
+            .iter()
+            .map(|formula| formula.as_ref().is_some_and(|f| f.eval(&space, &cell)))
+            .collect();
+        let first = hits.iter().position(|&hit| hit);
+        match first {
+            Some(first) => reachable[first] = true,
+            None => default_reachable = true,
+        }
+        for (j, _) in hits.iter().enumerate().filter(|(_, &hit)| hit) {
+            satisfiable[j] = true;
+            for i in 0..j {
+                overlapping[j][i] |= hits[i];
+                implying[j][i] &= hits[i];
+            }
+        }
+
+        if first.is_none() {
+            if let Some(span) = options.exhaustive {
+                return Err(syn::Error::new(
+                    span,
+                    format!("the conditions do not cover `{}`", space.describe(&cell)),
+                ));
+            }
+        }
+        if let (Some(first), Some(_)) = (first, options.disjoint) {
+            if let Some(second) = (first + 1..arms).find(|&j| hits[j]) {
+                return Err(syn::Error::new_spanned(
+                    &table.arms[second].condition,
+                    format!(
+                        "the conditions of {} and {} overlap at `{}`",
+                        describe_arm(table, first),
+                        describe_arm(table, second),
+                        space.describe(&cell),
+                    ),
+                ));
+            }
+        }
+    }
+
+    for j in (0..arms).filter(|&j| formulas[j].is_some() && !reachable[j]) {
+        let message = if !satisfiable[j] {
+            format!("{} can never match", describe_arm(table, j))
+        } else if let Some(i) = (0..j).find(|&i| implying[j][i]) {
+            format!(
+                "{} is shadowed by {}",
+                describe_arm(table, j),
+                describe_arm(table, i)
+            )
+        } else {
+            let earlier: Vec<String> = (0..j)
+                .filter(|&i| overlapping[j][i])
+                .map(|i| describe_arm(table, i))
+                .collect();
+            format!(
+                "{} is shadowed by {}",
+                describe_arm(table, j),
+                earlier.join(" together with ")
+            )
+        };
+        analysis.arm_warnings[j] = warning(table.arms[j].condition.span(), &message);
+    }
+    if let (Some(default), false) = (&table.default, default_reachable) {
+        analysis.default_warning = warning(
+            default.span(),
+            "the default arm is unreachable; the conditions above cover every input",
+        );
+    }
+    Ok(analysis)
+}
+
+fn describe_arm(table: &Table, index: usize) -> String {
+    format!(
+        "arm {} (`{}`)",
+        index + 1,
+        table.arms[index].condition.to_token_stream()
+    )
+}
+
+/// Emits `message` as a warning at `span`.
+///
+/// Procedural macros cannot emit warnings on stable Rust, so this goes through the deprecation
+/// lint: the generated code uses a deprecated function whose note is the message.
+fn warning(span: Span, message: &str) -> TokenStream {
+    quote_spanned! {span=>
+        {
+            #[deprecated(note = #message)]
+            fn unreachable_cond_arm() {}
+            unreachable_cond_arm();
+        }
+    }
+}
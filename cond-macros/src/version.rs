@@ -0,0 +1,224 @@
+//! `cond_version!`: arms selected by the version of the compiler.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Expr, Ident, Lit, Token};
+
+/// The release channel of a compiler.
+#[derive(Clone, Copy, PartialEq)]
+enum Channel {
+    Stable,
+    Beta,
+    Nightly,
+}
+
+/// The compiler this crate was built with, which is the one expanding the macro.
+struct Rustc {
+    version: (u32, u32, u32),
+    channel: Channel,
+}
+
+const RUSTC: Rustc = include!(concat!(env!("OUT_DIR"), "/rustc_version.rs"));
+
+/// A parsed `cond_version!` invocation.
+pub struct CondVersion {
+    arms: Vec<(Predicate, Expr)>,
+    default: Option<Expr>,
+}
+
+/// A condition on the compiler.
+enum Predicate {
+    Const(bool),
+    Channel(Channel),
+    Compare(Comparison, (u32, u32, u32)),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
+impl Parse for CondVersion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut arms = Vec::new();
+        let mut default = None;
+        while !input.is_empty() {
+            if input.peek(Token![_]) {
+                input.parse::<Token![_]>()?;
+                input.parse::<Token![=>]>()?;
+                default = Some(input.parse()?);
+                input.parse::<Option<Token![,]>>()?;
+                if !input.is_empty() {
+                    return Err(input.error("the default arm must come last"));
+                }
+                break;
+            }
+            let predicate = Predicate::parse_or(input)?;
+            input.parse::<Token![=>]>()?;
+            arms.push((predicate, input.parse()?));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(CondVersion { arms, default })
+    }
+}
+
+impl Predicate {
+    fn parse_or(input: ParseStream) -> syn::Result<Predicate> {
+        let mut predicate = Predicate::parse_and(input)?;
+        while input.peek(Token![||]) {
+            input.parse::<Token![||]>()?;
+            predicate = Predicate::Or(Box::new(predicate), Box::new(Predicate::parse_and(input)?));
+        }
+        Ok(predicate)
+    }
+
+    fn parse_and(input: ParseStream) -> syn::Result<Predicate> {
+        let mut predicate = Predicate::parse_unary(input)?;
+        while input.peek(Token![&&]) {
+            input.parse::<Token![&&]>()?;
+            predicate = Predicate::And(
+                Box::new(predicate),
+                Box::new(Predicate::parse_unary(input)?),
+            );
+        }
+        Ok(predicate)
+    }
+
+    fn parse_unary(input: ParseStream) -> syn::Result<Predicate> {
+        // `!=` must be checked before `!`.
+        if input.peek(Token![!]) && !input.peek(Token![!=]) {
+            input.parse::<Token![!]>()?;
+            return Ok(Predicate::Not(Box::new(Predicate::parse_unary(input)?)));
+        }
+        if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let predicate = Predicate::parse_or(&content)?;
+            if !content.is_empty() {
+                return Err(content.error("expected `)`"));
+            }
+            return Ok(predicate);
+        }
+        if input.peek(syn::LitBool) {
+            return Ok(Predicate::Const(input.parse::<syn::LitBool>()?.value));
+        }
+        if input.peek(Ident) {
+            let ident: Ident = input.parse()?;
+            let channel = match ident.to_string().as_str() {
+                "stable" => Channel::Stable,
+                "beta" => Channel::Beta,
+                "nightly" => Channel::Nightly,
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "expected `stable`, `beta` or `nightly`",
+                    ))
+                }
+            };
+            return Ok(Predicate::Channel(channel));
+        }
+        let comparison = Comparison::parse(input)?;
+        Ok(Predicate::Compare(comparison, parse_version(input)?))
+    }
+
+    fn eval(&self, rustc: &Rustc) -> bool {
+        match self {
+            Predicate::Const(value) => *value,
+            Predicate::Channel(channel) => rustc.channel == *channel,
+            Predicate::Compare(comparison, version) => {
+                let ordering = rustc.version.cmp(version);
+                match comparison {
+                    Comparison::Lt => ordering.is_lt(),
+                    Comparison::Le => ordering.is_le(),
+                    Comparison::Eq => ordering.is_eq(),
+                    Comparison::Ne => ordering.is_ne(),
+                    Comparison::Ge => ordering.is_ge(),
+                    Comparison::Gt => ordering.is_gt(),
+                }
+            }
+            Predicate::Not(inner) => !inner.eval(rustc),
+            Predicate::And(left, right) => left.eval(rustc) && right.eval(rustc),
+            Predicate::Or(left, right) => left.eval(rustc) || right.eval(rustc),
+        }
+    }
+}
+
+impl Comparison {
+    fn parse(input: ParseStream) -> syn::Result<Comparison> {
+        let lookahead = input.lookahead1();
+        let comparison = if lookahead.peek(Token![>=]) {
+            input.parse::<Token![>=]>()?;
+            Comparison::Ge
+        } else if lookahead.peek(Token![<=]) {
+            input.parse::<Token![<=]>()?;
+            Comparison::Le
+        } else if lookahead.peek(Token![==]) {
+            input.parse::<Token![==]>()?;
+            Comparison::Eq
+        } else if lookahead.peek(Token![!=]) {
+            input.parse::<Token![!=]>()?;
+            Comparison::Ne
+        } else if lookahead.peek(Token![>]) {
+            input.parse::<Token![>]>()?;
+            Comparison::Gt
+        } else if lookahead.peek(Token![<]) {
+            input.parse::<Token![<]>()?;
+            Comparison::Lt
+        } else {
+            return Err(lookahead.error());
+        };
+        Ok(comparison)
+    }
+}
+
+/// Parses a version such as `1`, `1.75` or `1.75.1`. Omitted components are 0.
+fn parse_version(input: ParseStream) -> syn::Result<(u32, u32, u32)> {
+    let span = input.span();
+    let invalid = || syn::Error::new(span, "expected a version such as `1.75` or `1.75.1`");
+    let mut text = match input.parse::<Lit>().map_err(|_| invalid())? {
+        Lit::Int(int) if int.suffix().is_empty() => int.base10_digits().to_owned(),
+        Lit::Float(float) if float.suffix().is_empty() => float.base10_digits().to_owned(),
+        _ => return Err(invalid()),
+    };
+    // `1.75.1` is a float literal followed by `.1`.
+    if input.peek(Token![.]) {
+        input.parse::<Token![.]>()?;
+        match input.parse::<Lit>().map_err(|_| invalid())? {
+            Lit::Int(int) if int.suffix().is_empty() => {
+                text.push('.');
+                text.push_str(int.base10_digits());
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    let mut parts = text.split('.').map(str::parse::<u32>);
+    let mut next = || parts.next().unwrap_or(Ok(0)).map_err(|_| invalid());
+    let version = (next()?, next()?, next()?);
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(version)
+}
+
+/// Expands to the value of the first arm whose condition holds for the compiler, or the default.
+pub fn expand(table: CondVersion) -> TokenStream {
+    let chosen = table
+        .arms
+        .iter()
+        .find(|(predicate, _)| predicate.eval(&RUSTC))
+        .map(|(_, value)| value)
+        .or(table.default.as_ref());
+    quote!({ #chosen })
+}
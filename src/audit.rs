@@ -0,0 +1,330 @@
+//! An audit trail of the decisions made by [`cond_audit!`](crate::cond_audit) tables.
+//!
+//! Each evaluation of an audited table passes a [`Record`] of the arm it selected, and optionally
+//! the inputs it was given, to an [`AuditSink`]. Sinks decide where records go: a database, a
+//! file, a log, or, with the `std` feature, an in-memory [`AuditLog`].
+//!
+//! A sink can also force decisions onto evaluations instead of letting the conditions decide, so
+//! a recorded sequence of decisions can be reproduced in a test or a debugger. With the `std`
+//! feature, [`Replay`] does this for entries from an [`AuditLog`].
+//!
+//! With the `std` feature, [`Sampled`] passes only a sample of the records on to another sink, at
+//! rates that can be changed while the program runs.
+
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{collections::VecDeque, format, string::String, sync::Mutex, vec::Vec};
+
+#[cfg(feature = "std")]
+use crate::clock::{Clock, InstantClock};
+#[cfg(feature = "std")]
+use crate::sample::Sampler;
+
+/// One evaluation of an audited table.
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    /// The identifier given to the table.
+    pub site: &'static str,
+    /// The index of the selected arm, or `None` if the default arm was selected or no condition
+    /// held.
+    pub arm: Option<usize>,
+    /// The source of the selected arm's condition, or `None` if no condition held.
+    pub condition: Option<&'static str>,
+    /// The source and value of each captured input.
+    pub inputs: &'a [(&'static str, &'a dyn Debug)],
+}
+
+/// A destination for audit records.
+pub trait AuditSink {
+    /// Stores a record. This is called before the selected arm's value is evaluated.
+    fn record(&self, record: &Record<'_>);
+
+    /// Returns a decision to use for the next evaluation of `site` instead of evaluating its
+    /// conditions, in the form of [`Record::arm`], or `None` to evaluate them. The default
+    /// implementation always returns `None`.
+    fn replay(&self, site: &'static str) -> Option<Option<usize>> {
+        let _ = site;
+        None
+    }
+}
+
+impl<F: Fn(&Record<'_>)> AuditSink for F {
+    fn record(&self, record: &Record<'_>) {
+        self(record)
+    }
+}
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but reports which arm was selected to an
+/// [`AuditSink`](crate::audit::AuditSink).
+///
+/// The first arguments are the sink, an identifier for the table, and optionally
+/// `inputs(...)` with expressions whose [`Debug`](core::fmt::Debug) representations are captured
+/// in the record, followed by a semicolon. The sink is borrowed, and the inputs are evaluated
+/// after the conditions. If the sink [replays](crate::audit::AuditSink::replay) a decision, the
+/// conditions are not evaluated at all.
+///
+/// ```
+/// # use cond::cond_audit;
+/// use cond::audit::Record;
+/// use std::cell::RefCell;
+///
+/// let trail = RefCell::new(Vec::new());
+/// let sink = |record: &Record| {
+///     trail.borrow_mut().push(format!("{}: {:?} {:?}", record.site, record.condition, record.inputs));
+/// };
+///
+/// let (amount, limit) = (1200, 1000);
+/// let approved = cond_audit! { sink, "credit.limit", inputs(amount, limit);
+///     amount > limit => false,
+///     _ => true,
+/// };
+/// assert!(!approved);
+/// assert_eq!(trail.borrow()[0], r#"credit.limit: Some("amount > limit") [("amount", 1200), ("limit", 1000)]"#);
+/// ```
+macro_rules! cond_audit {
+    (
+        $sink:expr, $site:expr $(, inputs($($input:expr),* $(,)?))?;
+        $($condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?
+    ) => {{
+        // Method calls, so that references to sinks and trait objects work too.
+        #[allow(unused_imports)]
+        use $crate::audit::AuditSink as _;
+        let sink = &$sink;
+        let site = $site;
+        let conditions: &[&'static str] = &[$(::core::stringify!($condition)),*];
+        let chosen: ::core::option::Option<usize> = match sink.replay(site) {
+            ::core::option::Option::Some(forced) => forced,
+            ::core::option::Option::None => {
+                let mut index = 0;
+                $(if { index += 1; $condition } { ::core::option::Option::Some(index - 1) } else)*
+                { ::core::option::Option::None }
+            }
+        };
+        sink.record(&$crate::audit::Record {
+            site,
+            arm: chosen,
+            condition: chosen.and_then(|arm| conditions.get(arm).copied()),
+            inputs: &[$($((::core::stringify!($input), &$input as &dyn ::core::fmt::Debug)),*)?],
+        });
+        let mut arm = 0;
+        $(if { arm += 1; chosen == ::core::option::Option::Some(arm - 1) } { $value } else)*
+        { $($default)? }
+    }};
+}
+
+/// One stored evaluation in an [`AuditLog`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// When the evaluation happened, according to the log's clock.
+    pub time: Duration,
+    /// The identifier given to the table.
+    pub site: &'static str,
+    /// The index of the selected arm, or `None` if no condition held.
+    pub arm: Option<usize>,
+    /// The source of the selected arm's condition, or `None` if no condition held.
+    pub condition: Option<&'static str>,
+    /// The source and [`Debug`] representation of each captured input.
+    pub inputs: Vec<(&'static str, String)>,
+}
+
+/// An in-memory audit sink that timestamps every record.
+///
+/// ```
+/// # use cond::cond_audit;
+/// use cond::audit::AuditLog;
+///
+/// let log: AuditLog = AuditLog::default();
+/// for attempts in [1, 5] {
+///     cond_audit! { log, "login.lockout", inputs(attempts);
+///         attempts >= 3 => "locked",
+///         _ => "allowed",
+///     };
+/// }
+/// let arms: Vec<_> = log.entries().iter().map(|entry| entry.arm).collect();
+/// assert_eq!(arms, [None, Some(0)]);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct AuditLog<C = InstantClock> {
+    clock: C,
+    entries: Mutex<Vec<Entry>>,
+}
+
+#[cfg(feature = "std")]
+impl<C: Clock> AuditLog<C> {
+    /// Creates an empty log that timestamps records with `clock`.
+    pub fn new(clock: C) -> Self {
+        AuditLog {
+            clock,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a copy of the entries recorded so far, oldest first.
+    pub fn entries(&self) -> Vec<Entry> {
+        self.lock().clone()
+    }
+
+    /// Removes and returns the entries recorded so far, oldest first.
+    pub fn take(&self) -> Vec<Entry> {
+        core::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Entry>> {
+        // A panic while holding the lock cannot leave the entries half-written.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C: Clock> AuditSink for AuditLog<C> {
+    fn record(&self, record: &Record<'_>) {
+        let entry = Entry {
+            time: self.clock.now(),
+            site: record.site,
+            arm: record.arm,
+            condition: record.condition,
+            inputs: record
+                .inputs
+                .iter()
+                .map(|(source, value)| (*source, format!("{value:?}")))
+                .collect(),
+        };
+        self.lock().push(entry);
+    }
+}
+
+/// A sink that forces recorded decisions onto evaluations, for reproducing a sequence of decisions
+/// exactly.
+///
+/// Each evaluation of a site takes the oldest remaining entry for that site. Sites without
+/// remaining entries are evaluated normally. Records are discarded.
+///
+/// ```
+/// # use cond::cond_audit;
+/// use cond::audit::{AuditLog, Replay};
+///
+/// fn route(sink: &impl cond::audit::AuditSink, load: u32) -> &'static str {
+///     cond_audit! { sink, "route";
+///         load > 80 => "overflow",
+///         _ => "primary",
+///     }
+/// }
+///
+/// let log: AuditLog = AuditLog::default();
+/// assert_eq!(route(&log, 95), "overflow");
+/// assert_eq!(route(&log, 10), "primary");
+///
+/// // Reproduce the recorded decisions, whatever the load is now.
+/// let replay = Replay::new(log.take());
+/// assert_eq!(route(&replay, 0), "overflow");
+/// assert_eq!(route(&replay, 0), "primary");
+/// assert_eq!(route(&replay, 99), "overflow");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Replay {
+    entries: Mutex<VecDeque<Entry>>,
+}
+
+#[cfg(feature = "std")]
+impl Replay {
+    /// Creates a sink that replays `entries` in order.
+    pub fn new(entries: impl IntoIterator<Item = Entry>) -> Self {
+        Replay {
+            entries: Mutex::new(entries.into_iter().collect()),
+        }
+    }
+
+    /// Returns how many entries have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl AuditSink for Replay {
+    fn record(&self, _: &Record<'_>) {}
+
+    fn replay(&self, site: &'static str) -> Option<Option<usize>> {
+        let mut entries = self.lock();
+        let position = entries.iter().position(|entry| entry.site == site)?;
+        entries.remove(position).map(|entry| entry.arm)
+    }
+}
+
+/// A sink that passes a sample of the records to another sink, for tables evaluated too often to
+/// record every decision.
+///
+/// Replayed decisions are always passed through, so sampling does not change what a [`Replay`]
+/// reproduces.
+///
+/// ```
+/// # use cond::cond_audit;
+/// use cond::audit::{AuditLog, Sampled};
+/// use cond::sample::Rate;
+///
+/// let sink = Sampled::new(AuditLog::<cond::clock::InstantClock>::default());
+/// sink.sampler().set("cache.lookup", Rate::one_in(10).non_default_only());
+/// for key in 0..100 {
+///     cond_audit! { sink, "cache.lookup", inputs(key);
+///         key % 4 == 0 => "miss",
+///         _ => "hit",
+///     };
+/// }
+/// // Of the 25 misses, the 1st, 11th and 21st were recorded.
+/// let keys: Vec<_> = sink.inner().entries().into_iter().map(|entry| entry.inputs[0].1.clone()).collect();
+/// assert_eq!(keys, ["0", "40", "80"]);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct Sampled<S> {
+    sink: S,
+    sampler: Sampler,
+}
+
+#[cfg(feature = "std")]
+impl<S: AuditSink> Sampled<S> {
+    /// Wraps `sink`, initially passing every record to it.
+    pub const fn new(sink: S) -> Self {
+        Sampled {
+            sink,
+            sampler: Sampler::new(),
+        }
+    }
+
+    /// Returns the sampler deciding which records are passed on, for changing its rates.
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    /// Returns the wrapped sink.
+    pub fn inner(&self) -> &S {
+        &self.sink
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: AuditSink> AuditSink for Sampled<S> {
+    fn record(&self, record: &Record<'_>) {
+        if self.sampler.sample(record.site, record.arm) {
+            self.sink.record(record);
+        }
+    }
+
+    fn replay(&self, site: &'static str) -> Option<Option<usize>> {
+        self.sink.replay(site)
+    }
+}
@@ -0,0 +1,102 @@
+//! Evaluation of [`cond!`](crate::cond) tables over collections.
+
+#[macro_export]
+/// Evaluates a [`cond!`](crate::cond) table for every element of an input slice, writing each
+/// result to the element at the same index of an output slice.
+///
+/// The first argument is `pattern in input => output`, followed by a semicolon and the arms. Each
+/// element is copied out of `input` and bound to `pattern`, so the elements must be [`Copy`].
+/// `input` and `output` can be anything that can be sliced with `[..]`, such as arrays, vectors
+/// and slices, and must have the same length.
+///
+/// Adding `, chunks N` after the output processes the elements in groups of `N` with a loop of
+/// known length, which compilers unroll and vectorize more readily when the arms are cheap. The
+/// results are the same.
+///
+/// ```
+/// # use cond::cond_vec;
+/// let readings = [3.5_f32, -1.0, 12.0, 7.25, 0.0];
+/// let mut levels = [0u8; 5];
+/// cond_vec! { x in readings => levels, chunks 4;
+///     x > 10.0 => 2,
+///     x > 0.0 => 1,
+///     _ => 0,
+/// }
+/// assert_eq!(levels, [1, 0, 2, 1, 0]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+macro_rules! cond_vec {
+    ($element:pat in $input:expr => $output:expr, chunks $lanes:expr; $($arms:tt)*) => {{
+        let input = &$input[..];
+        let output = &mut $output[..];
+        ::core::assert_eq!(input.len(), output.len(), "input and output lengths differ");
+        const LANES: usize = $lanes;
+        let mut inputs = input.chunks_exact(LANES);
+        let mut outputs = output.chunks_exact_mut(LANES);
+        for (outputs, inputs) in (&mut outputs).zip(&mut inputs) {
+            for lane in 0..LANES {
+                let $element = inputs[lane];
+                outputs[lane] = $crate::cond! { $($arms)* };
+            }
+        }
+        for (output, input) in outputs.into_remainder().iter_mut().zip(inputs.remainder()) {
+            let $element = *input;
+            *output = $crate::cond! { $($arms)* };
+        }
+    }};
+    ($element:pat in $input:expr => $output:expr; $($arms:tt)*) => {{
+        let input = &$input[..];
+        let output = &mut $output[..];
+        ::core::assert_eq!(input.len(), output.len(), "input and output lengths differ");
+        for (output, input) in output.iter_mut().zip(input) {
+            let $element = *input;
+            *output = $crate::cond! { $($arms)* };
+        }
+    }};
+}
+
+#[macro_export]
+/// Finds the first element of a collection for which some condition of a table holds, and
+/// evaluates to its index and the value of that arm.
+///
+/// The first argument is `pattern in iterable`, followed by a semicolon and the arms, which have
+/// no default arm. Each element is bound to `pattern` in turn. The invocation evaluates to
+/// `Some((index, value))` for the first element that satisfies any condition, where `value` is
+/// the value of the first arm whose condition it satisfies, or to `None` if no element does.
+///
+/// ```
+/// # use cond::position_cond;
+/// enum Review { Large, Embargoed }
+/// struct Order { total: u32, country: &'static str }
+///
+/// let orders = [
+///     Order { total: 20, country: "NL" },
+///     Order { total: 5000, country: "NO" },
+///     Order { total: 10, country: "XX" },
+/// ];
+/// let flagged = position_cond! { order in &orders;
+///     order.country == "XX" => Review::Embargoed,
+///     order.total > 1000 => Review::Large,
+/// };
+/// assert!(matches!(flagged, Some((1, Review::Large))));
+/// ```
+macro_rules! position_cond {
+    ($element:pat in $iterable:expr; $($condition:expr => $value:expr),+ $(,)?) => {{
+        let mut found = ::core::option::Option::None;
+        for (index, $element) in ::core::iter::Iterator::enumerate(
+            ::core::iter::IntoIterator::into_iter($iterable),
+        ) {
+            let value = $(if $condition { ::core::option::Option::Some($value) } else)+ {
+                ::core::option::Option::None
+            };
+            if let ::core::option::Option::Some(value) = value {
+                found = ::core::option::Option::Some((index, value));
+                break;
+            }
+        }
+        found
+    }};
+}
@@ -0,0 +1,196 @@
+//! Small behavior trees, for decision logic that outgrows a flat [`cond!`](crate::cond) table.
+//!
+//! A tree is built from [`Node`]s and ticked against a context value, typically the state of the
+//! agent being controlled. Leaves are [`condition`]s, which test the context, and [`action`]s,
+//! which act on it. Inner nodes are built with [`selector!`](crate::selector), which succeeds with
+//! the first child that does not fail (so it reads like a `cond!` table), and
+//! [`sequence!`](crate::sequence), which fails with the first child that does not succeed.
+//!
+//! Trees are reactive: every tick starts again from the first child, so a higher-priority branch
+//! can preempt a running one. Nodes are stored inline, so trees need no allocation.
+//!
+//! ```
+//! use cond::behavior::{action, condition, Node, Status};
+//! use cond::{selector, sequence};
+//!
+//! struct Guard { health: u32, enemy_visible: bool, log: Vec<&'static str> }
+//!
+//! let mut tree = selector!(
+//!     sequence!(
+//!         condition(|g: &Guard| g.health < 20),
+//!         action(|g: &mut Guard| { g.log.push("retreat"); Status::Running }),
+//!     ),
+//!     sequence!(
+//!         condition(|g: &Guard| g.enemy_visible),
+//!         action(|g: &mut Guard| { g.log.push("attack"); Status::Success }),
+//!     ),
+//!     action(|g: &mut Guard| { g.log.push("patrol"); Status::Success }),
+//! );
+//!
+//! let mut guard = Guard { health: 100, enemy_visible: true, log: Vec::new() };
+//! assert_eq!(tree.tick(&mut guard), Status::Success);
+//! guard.health = 10;
+//! assert_eq!(tree.tick(&mut guard), Status::Running);
+//! assert_eq!(guard.log, ["attack", "retreat"]);
+//! ```
+
+/// The result of ticking a [`Node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// The node finished successfully.
+    Success,
+    /// The node finished unsuccessfully.
+    Failure,
+    /// The node has not finished yet and should be ticked again.
+    Running,
+}
+
+impl From<bool> for Status {
+    /// Converts `true` to [`Status::Success`] and `false` to [`Status::Failure`].
+    fn from(success: bool) -> Self {
+        if success {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+/// A node of a behavior tree over a context of type `C`.
+pub trait Node<C: ?Sized> {
+    /// Runs the node once.
+    fn tick(&mut self, context: &mut C) -> Status;
+}
+
+impl<C: ?Sized, N: Node<C> + ?Sized> Node<C> for &mut N {
+    fn tick(&mut self, context: &mut C) -> Status {
+        (**self).tick(context)
+    }
+}
+
+/// A leaf that succeeds when a predicate over the context holds. See [`condition`].
+#[derive(Debug, Clone, Copy)]
+pub struct Condition<F>(F);
+
+/// Creates a leaf that succeeds when `predicate` holds and fails otherwise.
+pub fn condition<C: ?Sized, F: FnMut(&C) -> bool>(predicate: F) -> Condition<F> {
+    Condition(predicate)
+}
+
+impl<C: ?Sized, F: FnMut(&C) -> bool> Node<C> for Condition<F> {
+    fn tick(&mut self, context: &mut C) -> Status {
+        (self.0)(context).into()
+    }
+}
+
+/// A leaf that acts on the context. See [`action`].
+#[derive(Debug, Clone, Copy)]
+pub struct Action<F>(F);
+
+/// Creates a leaf that runs `act` and reports the status it returns.
+pub fn action<C: ?Sized, F: FnMut(&mut C) -> Status>(act: F) -> Action<F> {
+    Action(act)
+}
+
+impl<C: ?Sized, F: FnMut(&mut C) -> Status> Node<C> for Action<F> {
+    fn tick(&mut self, context: &mut C) -> Status {
+        (self.0)(context)
+    }
+}
+
+/// A node that swaps the success and failure of its child. See [`invert`].
+#[derive(Debug, Clone, Copy)]
+pub struct Invert<N>(N);
+
+/// Creates a node that succeeds when `node` fails and fails when it succeeds.
+pub fn invert<N>(node: N) -> Invert<N> {
+    Invert(node)
+}
+
+impl<C: ?Sized, N: Node<C>> Node<C> for Invert<N> {
+    fn tick(&mut self, context: &mut C) -> Status {
+        match self.0.tick(context) {
+            Status::Success => Status::Failure,
+            Status::Failure => Status::Success,
+            Status::Running => Status::Running,
+        }
+    }
+}
+
+/// The children of a [`Selector`] or [`Sequence`]: tuples of up to twelve nodes.
+pub trait Children<C: ?Sized> {
+    /// Ticks the children in order until one returns a status other than `pass`, and returns that
+    /// status, or `pass` if every child returned it.
+    fn tick_until(&mut self, context: &mut C, pass: Status) -> Status;
+}
+
+macro_rules! impl_children {
+    ($($node:ident $index:tt),+) => {
+        impl<C: ?Sized, $($node: Node<C>),+> Children<C> for ($($node,)+) {
+            fn tick_until(&mut self, context: &mut C, pass: Status) -> Status {
+                $(
+                    let status = self.$index.tick(context);
+                    if status != pass {
+                        return status;
+                    }
+                )+
+                pass
+            }
+        }
+    };
+}
+
+impl_children!(A 0);
+impl_children!(A 0, B 1);
+impl_children!(A 0, B 1, D 2);
+impl_children!(A 0, B 1, D 2, E 3);
+impl_children!(A 0, B 1, D 2, E 3, F 4);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9, L 10);
+impl_children!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9, L 10, M 11);
+
+/// A node that ticks its children in order and returns the status of the first one that does not
+/// fail, or fails if they all do. See [`selector!`](crate::selector).
+#[derive(Debug, Clone, Copy)]
+pub struct Selector<T>(pub T);
+
+impl<C: ?Sized, T: Children<C>> Node<C> for Selector<T> {
+    fn tick(&mut self, context: &mut C) -> Status {
+        self.0.tick_until(context, Status::Failure)
+    }
+}
+
+/// A node that ticks its children in order and returns the status of the first one that does not
+/// succeed, or succeeds if they all do. See [`sequence!`](crate::sequence).
+#[derive(Debug, Clone, Copy)]
+pub struct Sequence<T>(pub T);
+
+impl<C: ?Sized, T: Children<C>> Node<C> for Sequence<T> {
+    fn tick(&mut self, context: &mut C) -> Status {
+        self.0.tick_until(context, Status::Success)
+    }
+}
+
+#[macro_export]
+/// Builds a [`Selector`](crate::behavior::Selector) node from up to twelve child nodes.
+///
+/// See the [`behavior`](crate::behavior) module for an example.
+macro_rules! selector {
+    ($($child:expr),+ $(,)?) => {
+        $crate::behavior::Selector(($($child,)+))
+    };
+}
+
+#[macro_export]
+/// Builds a [`Sequence`](crate::behavior::Sequence) node from up to twelve child nodes.
+///
+/// See the [`behavior`](crate::behavior) module for an example.
+macro_rules! sequence {
+    ($($child:expr),+ $(,)?) => {
+        $crate::behavior::Sequence(($($child,)+))
+    };
+}
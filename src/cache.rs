@@ -0,0 +1,198 @@
+//! Caching of the arm a table selects for an input.
+//!
+//! When a table is evaluated over and over for a small set of distinct inputs, and its conditions
+//! are expensive, an [`ArmCache`] remembers which arm each input selected, so the conditions only
+//! run the first time an input is seen. Only the arm is cached, so the values need not be
+//! [`Clone`] and are still computed for every evaluation.
+//!
+//! [`StaticCond::eval_cached`](crate::StaticCond::eval_cached) evaluates a static table through a
+//! cache.
+//!
+//! The cache assumes the selection depends on nothing but the input. If it depends on other state
+//! too, call [`invalidate`](ArmCache::invalidate) or [`clear`](ArmCache::clear) when that state
+//! changes.
+
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::vec::Vec;
+
+/// A bounded cache from inputs to the index of the arm they select, evicting the least recently
+/// used input when full.
+///
+/// ```
+/// use cond::cache::ArmCache;
+///
+/// let mut cache = ArmCache::new(128);
+/// let classify = |cache: &mut ArmCache<u32>, n: u32| {
+///     let arm = cache.arm(n, |&n| {
+///         // Stands in for expensive conditions.
+///         let conditions = [n % 15 == 0, n % 5 == 0, n % 3 == 0];
+///         conditions.iter().position(|&held| held)
+///     });
+///     match arm {
+///         Some(0) => "fizzbuzz".to_string(),
+///         Some(1) => "buzz".to_string(),
+///         Some(2) => "fizz".to_string(),
+///         _ => n.to_string(),
+///     }
+/// };
+///
+/// assert_eq!(classify(&mut cache, 30), "fizzbuzz");
+/// assert_eq!(classify(&mut cache, 30), "fizzbuzz");
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArmCache<K> {
+    capacity: usize,
+    index: HashMap<K, usize>,
+    // The cached inputs, linked from the most to the least recently used. Slots are reused rather
+    // than removed, so that indices stay valid, with `free` holding those of invalidated inputs.
+    slots: Vec<Slot<K>>,
+    free: Vec<usize>,
+    newest: usize,
+    oldest: usize,
+    // The address of the arms of the static table the cache is used with, if any.
+    table: Option<usize>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<K> {
+    key: K,
+    arm: Option<usize>,
+    newer: usize,
+    older: usize,
+}
+
+/// The end of the list of slots.
+const NONE: usize = usize::MAX;
+
+impl<K: Hash + Eq + Clone> ArmCache<K> {
+    /// Creates an empty cache holding at most `capacity` inputs. A capacity of 0 caches nothing.
+    pub fn new(capacity: usize) -> Self {
+        ArmCache {
+            capacity,
+            index: HashMap::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            newest: NONE,
+            oldest: NONE,
+            table: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the arm cached for `key`, or selects one with `select`, caches it and returns it.
+    ///
+    /// Lookups and evictions take constant time, whatever the capacity.
+    pub fn arm(&mut self, key: K, select: impl FnOnce(&K) -> Option<usize>) -> Option<usize> {
+        if let Some(&slot) = self.index.get(&key) {
+            self.hits += 1;
+            self.unlink(slot);
+            self.push_newest(slot);
+            return self.slots[slot].arm;
+        }
+        self.misses += 1;
+        let arm = select(&key);
+        if self.capacity == 0 {
+            return arm;
+        }
+        let slot = if self.index.len() >= self.capacity {
+            let slot = self.oldest;
+            self.unlink(slot);
+            self.index.remove(&self.slots[slot].key);
+            self.slots[slot].key = key.clone();
+            self.slots[slot].arm = arm;
+            slot
+        } else if let Some(slot) = self.free.pop() {
+            self.slots[slot].key = key.clone();
+            self.slots[slot].arm = arm;
+            slot
+        } else {
+            self.slots.push(Slot {
+                key: key.clone(),
+                arm,
+                newer: NONE,
+                older: NONE,
+            });
+            self.slots.len() - 1
+        };
+        self.push_newest(slot);
+        self.index.insert(key, slot);
+        arm
+    }
+
+    /// Forgets the arm cached for `key`, returning whether there was one.
+    pub fn invalidate(&mut self, key: &K) -> bool {
+        match self.index.remove(key) {
+            Some(slot) => {
+                self.unlink(slot);
+                self.free.push(slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forgets every cached arm.
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.slots.clear();
+        self.free.clear();
+        self.newest = NONE;
+        self.oldest = NONE;
+    }
+
+    /// Ties the cache to the static table whose arms are at `table`, forgetting the arms cached
+    /// for another table, whose indices would mean other arms.
+    pub(crate) fn tie_to(&mut self, table: usize) {
+        if self.table != Some(table) {
+            self.clear();
+            self.table = Some(table);
+        }
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let Slot { newer, older, .. } = self.slots[slot];
+        match newer {
+            NONE => self.newest = older,
+            newer => self.slots[newer].older = older,
+        }
+        match older {
+            NONE => self.oldest = newer,
+            older => self.slots[older].newer = newer,
+        }
+    }
+
+    fn push_newest(&mut self, slot: usize) {
+        self.slots[slot].newer = NONE;
+        self.slots[slot].older = self.newest;
+        match self.newest {
+            NONE => self.oldest = slot,
+            newest => self.slots[newest].newer = slot,
+        }
+        self.newest = slot;
+    }
+
+    /// Returns the number of cached inputs.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns whether no inputs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns how many lookups found a cached arm.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns how many lookups had to select an arm.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
@@ -0,0 +1,148 @@
+//! A procedural version of [`cond!`](crate::cond) that can analyze its conditions at compile time.
+//!
+//! [`checked::cond!`](cond) accepts `condition => value` arms and a last `_ => default` arm, like
+//! the declarative macro, and expands to the same `if`-`else` chain. The declarative macro's nested
+//! `=>>` tables, `@unless` arms and `_ (failed) =>` default arms are not supported. Because it sees
+//! the conditions as syntax, it can also prove properties about tables whose conditions only
+//! compare variables with integer literals, test boolean variables, and combine those with `!`,
+//! `&&` and `||`. Opt in with a `#[cond(...)]` attribute before the first arm:
+//!
+//! - `exhaustive`: the conditions must cover every input. The default arm is then omitted, even
+//!   when the arms produce values.
+//! - `disjoint`: no input may satisfy two conditions.
+//! - `domain(x: u8, ...)`: restricts integer variables to the range of a primitive type. Without
+//!   it, variables range over all integers.
+//! - `pure`: no condition may have obvious side effects; see [below](#pure-conditions). This
+//!   option works with any conditions.
+//!
+//! ```
+//! use cond::checked::cond;
+//!
+//! fn sign(x: i32) -> i32 {
+//!     cond! {
+//!         #[cond(exhaustive, disjoint)]
+//!         x < 0 => -1,
+//!         x == 0 => 0,
+//!         x > 0 => 1,
+//!     }
+//! }
+//! assert_eq!(sign(-7), -1);
+//! ```
+//!
+//! Forgetting zero is a compile error reporting `x = 0` as an uncovered input:
+//!
+//! ```compile_fail
+//! use cond::checked::cond;
+//!
+//! fn sign(x: i32) -> i32 {
+//!     cond! {
+//!         #[cond(exhaustive)]
+//!         x < 0 => -1,
+//!         x > 0 => 1,
+//!     }
+//! }
+//! ```
+//!
+//! Unsigned variables need a domain, otherwise negative values count as uncovered:
+//!
+//! ```
+//! use cond::checked::cond;
+//!
+//! fn describe(len: usize, empty_ok: bool) -> &'static str {
+//!     cond! {
+//!         #[cond(exhaustive, domain(len: usize))]
+//!         len == 0 && empty_ok => "fine",
+//!         len == 0 && !empty_ok => "missing",
+//!         len > 0 => "present",
+//!     }
+//! }
+//! assert_eq!(describe(0, false), "missing");
+//! ```
+//!
+//! # Shadowed arms
+//!
+//! Even without options, the macro looks for arms that can never be selected because earlier
+//! arms already match every input they would, and warns about each one, naming the arms that
+//! shadow it. A default arm that the conditions above it always preempt is reported as well.
+//! Conditions the analysis does not understand are ignored for this purpose.
+//!
+//! The warnings are reported through the `deprecated` lint, so they can be denied like any other:
+//!
+//! ```compile_fail
+//! #![deny(deprecated)]
+//! use cond::checked::cond;
+//!
+//! fn price(quantity: u32) -> u32 {
+//!     cond! {
+//!         quantity > 5 => 8,
+//!         // error: arm 2 (`quantity > 10`) is shadowed by arm 1 (`quantity > 5`)
+//!         quantity > 10 => 7,
+//!         _ => 10,
+//!     }
+//! }
+//! ```
+//!
+//! # Pure conditions
+//!
+//! A condition only runs when every condition before it failed, so a condition that changes
+//! something, such as `queue.pop().is_some()`, silently does nothing whenever an earlier arm
+//! matches. With `#[cond(pure)]`, conditions containing assignments, `&mut` borrows, `.await`,
+//! output or panicking macros, or calls to methods that usually mutate their receiver, such as
+//! `push`, `pop`, `next`, `insert` or `take`, are compile errors. Methods are recognized by name
+//! only, so this catches the common mistakes rather than proving that a table is pure.
+//!
+//! ```compile_fail
+//! use cond::checked::cond;
+//!
+//! fn step(urgent: bool, jobs: &mut Vec<u32>) -> &'static str {
+//!     cond! {
+//!         #[cond(pure)]
+//!         urgent => "urgent",
+//!         // error: the condition of arm 2 (`jobs.pop().is_some()`) has a call to `pop`, ...
+//!         jobs.pop().is_some() => "job",
+//!         _ => "idle",
+//!     }
+//! }
+//! ```
+//!
+//! # Priorities
+//!
+//! Arms can be given explicit priorities with `#[priority(n)]`, for tables that are generated or
+//! assembled by other macros, where the order of the tokens is not easy to control. The arms are
+//! then checked from the highest priority to the lowest, whatever order they are written in. If
+//! one arm has a priority, every arm must, and no two arms may share one. The other analyses
+//! number the arms in the order they are checked.
+//!
+//! ```
+//! use cond::checked::cond;
+//!
+//! fn shipping(weight: u32, express: bool) -> u32 {
+//!     cond! {
+//!         #[priority(1)]
+//!         weight > 20 => 40,
+//!         #[priority(2)]
+//!         express => 25,
+//!         #[priority(0)]
+//!         weight > 0 => 10,
+//!         _ => 0,
+//!     }
+//! }
+//! assert_eq!(shipping(30, true), 25);
+//! ```
+//!
+//! ```compile_fail
+//! use cond::checked::cond;
+//!
+//! fn shipping(weight: u32) -> u32 {
+//!     cond! {
+//!         #[priority(1)]
+//!         weight > 20 => 40,
+//!         // error: another arm already has priority 1
+//!         #[priority(1)]
+//!         weight > 0 => 10,
+//!         _ => 0,
+//!     }
+//! }
+//! ```
+
+pub use cond_macros::cond;
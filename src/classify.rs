@@ -0,0 +1,41 @@
+//! A derive for classifying a struct by thresholds on its fields.
+
+/// Derives a `classify(&self)` method from `#[classify(...)]` tables on the fields of a struct
+/// with named fields, so that thresholds live next to the data they apply to instead of in a
+/// separate method.
+///
+/// Each field attribute holds arms like those of [`cond!`](crate::cond). The method evaluates the
+/// arms of every field in the order the fields are declared, and returns the value of the first
+/// arm whose condition holds. In the conditions, each field with a `#[classify(...)]` attribute is
+/// bound by value under its own name, so those fields must be [`Copy`]; other fields are reached
+/// through `self`.
+///
+/// The struct needs a `#[classify(output = Type)]` attribute naming the type of the values. With
+/// `default = value` as well, the method returns `Type`, and the default when no condition holds;
+/// without one, it returns `Option<Type>`.
+///
+/// ```
+/// use cond::Classify;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Severity { Critical, Warning, Ok }
+///
+/// #[derive(Classify)]
+/// #[classify(output = Severity, default = Severity::Ok)]
+/// struct Host {
+///     name: String,
+///     #[classify(cpu > 90.0 => Severity::Critical, cpu > 70.0 => Severity::Warning)]
+///     cpu: f64,
+///     #[classify(
+///         disk_free < 0.05 => Severity::Critical,
+///         disk_free < 0.2 && !self.name.starts_with("scratch") => Severity::Warning,
+///     )]
+///     disk_free: f64,
+/// }
+///
+/// let host = |name: &str, cpu, disk_free| Host { name: name.to_string(), cpu, disk_free };
+/// assert_eq!(host("db", 95.0, 0.5).classify(), Severity::Critical);
+/// assert_eq!(host("db", 20.0, 0.1).classify(), Severity::Warning);
+/// assert_eq!(host("scratch-1", 20.0, 0.1).classify(), Severity::Ok);
+/// ```
+pub use cond_macros::Classify;
@@ -0,0 +1,116 @@
+//! Time sources for the stateful conditions in [`gate`](crate::gate).
+//!
+//! Conditions that depend on time take a [`Clock`] instead of reading the system time, so they can
+//! be driven by a hardware timer on embedded targets, by a tick counter in a game loop, or by a
+//! [`ManualClock`] in tests.
+
+use core::cell::Cell;
+use core::time::Duration;
+
+/// A monotonic time source.
+pub trait Clock {
+    /// Returns the time elapsed since an arbitrary, fixed origin. It should never decrease, but
+    /// if it does, as a [`SystemClock`] can, gates treat the time in between as not having passed.
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// A clock that only moves when told to, for tests and tick-driven loops.
+///
+/// Gates usually take the clock by reference, so it can be advanced while they use it:
+///
+/// ```
+/// use cond::clock::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let reader = &clock;
+/// clock.advance(Duration::from_secs(2));
+/// assert_eq!(reader.now(), Duration::from_secs(2));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock(Cell<Duration>);
+
+impl ManualClock {
+    /// Creates a clock at time zero.
+    pub const fn new() -> Self {
+        ManualClock(Cell::new(Duration::ZERO))
+    }
+
+    /// Moves the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+
+    /// Moves the clock to `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to` is earlier than the current time.
+    pub fn set(&self, to: Duration) {
+        assert!(to >= self.0.get(), "a clock must not go backwards");
+        self.0.set(to);
+    }
+
+    /// Moves the clock back by `by`, or to time zero if it is earlier than that, for testing how
+    /// gates cope with clocks that go backwards.
+    pub fn rewind(&self, by: Duration) {
+        self.0.set(self.0.get().saturating_sub(by));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+/// A clock backed by [`std::time::Instant`], measuring from when it was created.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct InstantClock(std::time::Instant);
+
+#[cfg(feature = "std")]
+impl InstantClock {
+    /// Creates a clock whose origin is the current instant.
+    pub fn new() -> Self {
+        InstantClock(std::time::Instant::now())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for InstantClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for InstantClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// A clock backed by [`std::time::SystemTime`], measuring from the Unix epoch, for
+/// [`Schedule`](crate::schedule::Schedule).
+///
+/// Unlike the other clocks, it goes backwards if the system time is set back, so gates that measure
+/// intervals should use an [`InstantClock`] instead.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,63 @@
+//! A variant of [`cond!`](crate::cond) that reports how it was evaluated.
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but prints the source and result of every condition it evaluates
+/// to standard error, followed by the arm it selected, like [`dbg!`](std::dbg) does for an
+/// expression.
+///
+/// Every line starts with the file, line and column of the invocation. The selected arm is shown
+/// by its condition, or as `_` for the default arm or when no condition held. Like `dbg!`, it is
+/// meant for finding out why a table picks the arm it does, not to be left in. Without debug
+/// assertions, as in release builds of the crate invoking it, it prints nothing and evaluates
+/// like `cond!`. Tables with nested arms are not supported.
+///
+/// ```
+/// # use cond::cond_dbg;
+/// let (attempts, locked) = (2, false);
+/// // prints:
+/// // [src/main.rs:6:14] locked = false
+/// // [src/main.rs:6:14] attempts >= 5 = false
+/// // [src/main.rs:6:14] => _
+/// let action = cond_dbg! {
+///     locked => "reject",
+///     attempts >= 5 => "lock",
+///     _ => "retry",
+/// };
+/// assert_eq!(action, "retry");
+/// ```
+macro_rules! cond_dbg {
+    ($($condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?) => {{
+        // Debug assertions are checked here, so the profile of the invoking crate decides.
+        let location = ::core::cfg!(debug_assertions)
+            .then(|| (::core::file!(), ::core::line!(), ::core::column!()));
+        $(if $crate::__private::dbg_condition(location, ::core::stringify!($condition), $condition) {
+            $crate::__private::dbg_selected(location, ::core::stringify!($condition));
+            $value
+        } else)* {
+            $crate::__private::dbg_selected(location, "_");
+            $($default)?
+        }
+    }};
+}
+
+/// Where a [`cond_dbg!`] table was invoked: its file, line and column.
+type Location = (&'static str, u32, u32);
+
+/// Prints a condition and its result if there is a location, and returns the result.
+#[doc(hidden)]
+#[inline(always)]
+pub fn dbg_condition(location: Option<Location>, source: &str, held: bool) -> bool {
+    if let Some((file, line, column)) = location {
+        std::eprintln!("[{file}:{line}:{column}] {source} = {held}");
+    }
+    held
+}
+
+/// Prints the condition of the selected arm if there is a location.
+#[doc(hidden)]
+#[inline(always)]
+pub fn dbg_selected(location: Option<Location>, source: &str) {
+    if let Some((file, line, column)) = location {
+        std::eprintln!("[{file}:{line}:{column}] => {source}");
+    }
+}
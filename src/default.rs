@@ -0,0 +1,27 @@
+//! A variant of [`cond!`](crate::cond) that falls back to [`Default::default()`].
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but evaluates to [`Default::default()`] when no condition holds,
+/// instead of requiring a default arm.
+///
+/// The value type must implement [`Default`].
+///
+/// ```
+/// # use cond::cond_or_default;
+/// let (retries, timed_out) = (4, false);
+/// let message: String = cond_or_default! {
+///     timed_out => "timed out".to_string(),
+///     retries > 3 => format!("gave up after {retries} retries"),
+/// };
+/// assert_eq!(message, "gave up after 4 retries");
+///
+/// let bonus: u32 = cond_or_default! { retries == 0 => 10 };
+/// assert_eq!(bonus, 0);
+/// ```
+macro_rules! cond_or_default {
+    ($($condition:expr => $value:expr),+ $(,)?) => {
+        $(if $condition { $value } else)+ {
+            ::core::default::Default::default()
+        }
+    };
+}
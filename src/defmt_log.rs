@@ -0,0 +1,21 @@
+//! Logging of the arms selected by [`cond_named!`](crate::cond_named) tables with `defmt`.
+//!
+//! With the `defmt` feature, every evaluation of a named table logs the table name and the name of
+//! the selected arm, or `_` for the default arm or when no arm matched, at the debug level. Both
+//! names are interned strings, so the log frame is two string indices, with no formatting on the
+//! device, which suits RTT logging on microcontrollers.
+//!
+//! The messages come from this crate, so `defmt`'s filter enables them by its name, as in
+//! `DEFMT_LOG=cond=debug`, and they cost no code at all when filtered out. The names are interned
+//! in the crate invoking the table, which must depend on `defmt` too, as any crate with a `defmt`
+//! logger does.
+//!
+//! Arms forced with [`testkit::force`](crate::testkit::force) evaluate no conditions and are not
+//! logged.
+
+/// Logs the arm `arm` selected by the table `site`.
+#[doc(hidden)]
+#[inline]
+pub fn matched(site: defmt::Str, arm: defmt::Str) {
+    defmt::debug!("{=istr}: {=istr}", site, arm);
+}
@@ -0,0 +1,104 @@
+//! Selection on `const` boolean parameters at monomorphization time.
+//!
+//! A plain `if B { .. } else { .. }` on a const parameter is usually optimized down to one branch,
+//! but both branches are still compiled into every instantiation, and are only removed if the
+//! optimizer runs. Dispatching through [`Bool`] instead only instantiates the chosen branch, and
+//! [`Select`] picks between types, which no `if` can do:
+//!
+//! ```
+//! use cond::const_cond;
+//! use cond::dispatch::{Bool, Dispatch, Select};
+//!
+//! struct Accumulator<const WIDE: bool>
+//! where
+//!     Bool<WIDE>: Dispatch,
+//! {
+//!     total: Select<WIDE, u64, u32>,
+//! }
+//!
+//! fn sum<const SATURATE: bool, const CHECKED: bool>(items: &[u8]) -> Option<u8>
+//! where
+//!     Bool<SATURATE>: Dispatch,
+//!     Bool<CHECKED>: Dispatch,
+//! {
+//!     items.iter().try_fold(0u8, |total, &item| const_cond! {
+//!         SATURATE => Some(total.saturating_add(item)),
+//!         CHECKED => total.checked_add(item),
+//!         _ => Some(total.wrapping_add(item)),
+//!     })
+//! }
+//!
+//! let wide = Accumulator::<true> { total: u64::MAX };
+//! assert_eq!(wide.total, u64::MAX);
+//! assert_eq!(sum::<true, false>(&[200, 100]), Some(255));
+//! assert_eq!(sum::<false, true>(&[200, 100]), None);
+//! assert_eq!(sum::<false, false>(&[200, 100]), Some(44));
+//! ```
+//!
+//! Rust cannot see that both `Bool<true>` and `Bool<false>` implement [`Dispatch`], so generic
+//! code needs a `Bool<B>: Dispatch` bound for each parameter it dispatches on.
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but with conditions that are `const` boolean parameters or
+/// constants, and with only the chosen arm compiled into each instantiation.
+///
+/// Each condition becomes the argument of a [`Bool`](crate::dispatch::Bool), so it must be a
+/// const parameter, a constant, or a constant expression that does not use generic parameters,
+/// and generic code needs a `Bool<B>: Dispatch` bound for it. Each value becomes a closure, so the
+/// arms cannot use `return`, `break` or `?` on the surrounding function, and a value that is moved
+/// into an arm cannot be moved into another one. See [`dispatch`](crate::dispatch) for an example.
+macro_rules! const_cond {
+    (_ => $default:expr $(,)?) => {
+        $default
+    };
+    ($condition:expr => $value:expr $(,)?) => {
+        $crate::const_cond!($condition => $value, _ => ())
+    };
+    ($condition:expr => $value:expr, $($rest:tt)+) => {
+        <$crate::dispatch::Bool<{ $condition }> as $crate::dispatch::Dispatch>::dispatch(
+            || $value,
+            || $crate::const_cond!($($rest)+),
+        )
+    };
+}
+
+/// A boolean lifted to the type level.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bool<const B: bool>;
+
+/// Runs one of two closures depending on the boolean of a [`Bool`], instantiating only that one.
+pub trait Dispatch {
+    /// The boolean.
+    const VALUE: bool;
+
+    /// The first of two types if the boolean is true, and the second otherwise.
+    type Select<T, F>;
+
+    /// Calls `if_true` if the boolean is true, and `if_false` otherwise.
+    fn dispatch<R>(if_true: impl FnOnce() -> R, if_false: impl FnOnce() -> R) -> R;
+}
+
+impl Dispatch for Bool<true> {
+    const VALUE: bool = true;
+
+    type Select<T, F> = T;
+
+    #[inline(always)]
+    fn dispatch<R>(if_true: impl FnOnce() -> R, _: impl FnOnce() -> R) -> R {
+        if_true()
+    }
+}
+
+impl Dispatch for Bool<false> {
+    const VALUE: bool = false;
+
+    type Select<T, F> = F;
+
+    #[inline(always)]
+    fn dispatch<R>(_: impl FnOnce() -> R, if_false: impl FnOnce() -> R) -> R {
+        if_false()
+    }
+}
+
+/// `T` if `B` is true, and `F` otherwise.
+pub type Select<const B: bool, T, F> = <Bool<B> as Dispatch>::Select<T, F>;
@@ -0,0 +1,26 @@
+//! Arms selected by build-time environment variables.
+
+/// Like [`cond!`](crate::cond), but with conditions on environment variables that are evaluated
+/// at compile time, so only the selected arm's value is compiled.
+///
+/// A condition can be `env("NAME")`, which holds when the variable is set; `env("NAME") == "value"`
+/// or `env("NAME") != "value"`; `true` or `false`; and combinations of those with `!`, `&&`, `||`
+/// and parentheses. The variables are those of the compiler's environment, as with
+/// [`option_env!`], and changing one makes Cargo rebuild the crate. If no condition holds and
+/// there is no default arm, the invocation expands to `()`.
+///
+/// Because only one value remains, the arms may have different types, and the invocation can be
+/// used in constants:
+///
+/// ```
+/// # use cond::cond_env;
+/// const MAX_LOG_LEVEL: u8 = cond_env! {
+///     env("APP_LOG") == "trace" => 4,
+///     env("APP_LOG") == "debug" || env("APP_VERBOSE") => 3,
+///     _ => 2,
+/// };
+/// # if option_env!("APP_LOG").is_none() && option_env!("APP_VERBOSE").is_none() {
+/// assert_eq!(MAX_LOG_LEVEL, 2);
+/// # }
+/// ```
+pub use cond_macros::cond_env;
@@ -0,0 +1,153 @@
+//! A fallible variant of [`cond!`](crate::cond) and its error type.
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but evaluates to a `Result`: `Ok` with the value of the first arm
+/// whose condition holds, or `Err` with a [`NoMatchError`](crate::NoMatchError) if none does.
+///
+/// No default arm is accepted. The error records the source of the conditions that were checked
+/// and the location of the invocation.
+///
+/// ```
+/// # use cond::try_cond;
+/// fn backend(primary_up: bool, secondary_up: bool) -> Result<&'static str, cond::NoMatchError> {
+///     try_cond! {
+///         primary_up => "primary",
+///         secondary_up => "secondary",
+///     }
+/// }
+///
+/// assert_eq!(backend(false, true), Ok("secondary"));
+/// let error = backend(false, false).unwrap_err();
+/// assert_eq!(error.conditions(), ["primary_up", "secondary_up"]);
+/// ```
+///
+/// With the `alloc` feature, a leading `with_context(...);` lists operands whose [`Debug`]
+/// renderings are captured in the error when no condition holds; see `NoMatchError::context`.
+///
+/// [`Debug`]: core::fmt::Debug
+macro_rules! try_cond {
+    (with_context($($operand:expr),+ $(,)?); $($condition:expr => $value:expr),* $(,)?) => {
+        $(if $condition { ::core::result::Result::Ok($value) } else)* {
+            ::core::result::Result::Err($crate::try_cond!(@error $($condition),*).with_context(
+                $crate::__private::vec![$(
+                    (::core::stringify!($operand), $crate::__private::format!("{:?}", $operand))
+                ),+]
+            ))
+        }
+    };
+    (@error $($condition:expr),*) => {
+        $crate::NoMatchError::new(
+            ::core::file!(),
+            ::core::line!(),
+            ::core::column!(),
+            &[$(::core::stringify!($condition)),*],
+        )
+    };
+    ($($condition:expr => $value:expr),* $(,)?) => {
+        $(if $condition { ::core::result::Result::Ok($value) } else)* {
+            ::core::result::Result::Err($crate::try_cond!(@error $($condition),*))
+        }
+    };
+}
+
+/// The error returned by [`try_cond!`](crate::try_cond) when no condition holds, and by
+/// [`parse_cond!`](crate::parse_cond) when no probe matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoMatchError {
+    file: &'static str,
+    line: u32,
+    column: u32,
+    conditions: &'static [&'static str],
+    #[cfg(feature = "alloc")]
+    context: Vec<(&'static str, String)>,
+}
+
+impl NoMatchError {
+    #[doc(hidden)]
+    pub fn new(
+        file: &'static str,
+        line: u32,
+        column: u32,
+        conditions: &'static [&'static str],
+    ) -> Self {
+        NoMatchError {
+            file,
+            line,
+            column,
+            conditions,
+            #[cfg(feature = "alloc")]
+            context: Vec::new(),
+        }
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "alloc")]
+    pub fn with_context(mut self, context: Vec<(&'static str, String)>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The source file of the macro invocation.
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// The line of the macro invocation.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The column of the macro invocation.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// The source of every condition that was checked, in order.
+    pub fn conditions(&self) -> &'static [&'static str] {
+        self.conditions
+    }
+
+    /// The operands named in `with_context(...)`, with their [`Debug`](fmt::Debug) renderings.
+    ///
+    /// ```
+    /// # use cond::try_cond;
+    /// let (load, retries) = (0.97, 5);
+    /// let result = try_cond! { with_context(load, retries);
+    ///     load < 0.8 => "accept",
+    ///     retries < 3 => "retry",
+    /// };
+    /// let error = result.unwrap_err();
+    /// assert_eq!(error.context(), [("load", "0.97".to_string()), ("retries", "5".to_string())]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn context(&self) -> &[(&'static str, String)] {
+        &self.context
+    }
+}
+
+impl fmt::Display for NoMatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no condition held at {}:{}:{}",
+            self.file, self.line, self.column
+        )?;
+        for (i, condition) in self.conditions.iter().enumerate() {
+            let separator = if i == 0 { "; checked " } else { ", " };
+            write!(f, "{}`{}`", separator, condition)?;
+        }
+        #[cfg(feature = "alloc")]
+        for (i, (name, value)) in self.context.iter().enumerate() {
+            let separator = if i == 0 { "; with " } else { ", " };
+            write!(f, "{}{} = {}", separator, name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for NoMatchError {}
@@ -0,0 +1,93 @@
+//! Variants of [`cond!`](crate::cond) that panic when no condition holds.
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but panics with a message instead of requiring a default arm.
+///
+/// The first argument is the panic message, followed by a semicolon, like [`Option::expect`].
+/// The panic reports the location of the `cond_expect!` invocation, and goes through a cold
+/// function, so the arms are laid out as the likely path.
+///
+/// ```
+/// # use cond::cond_expect;
+/// let quantity = 12;
+/// let discount = cond_expect! { "quantity must be positive";
+///     quantity >= 10 => 20,
+///     quantity >= 5 => 10,
+///     quantity >= 1 => 0,
+/// };
+/// assert_eq!(discount, 20);
+/// ```
+///
+/// ```should_panic
+/// # use cond::cond_expect;
+/// let quantity = 0;
+/// // panics with "quantity must be positive"
+/// let discount: u32 = cond_expect! { "quantity must be positive";
+///     quantity >= 1 => 0,
+/// };
+/// ```
+macro_rules! cond_expect {
+    ($message:expr; $($condition:expr => $value:expr),+ $(,)?) => {
+        $(if $condition { $value } else)+ {
+            $crate::__private::no_match($message)
+        }
+    };
+}
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but panics when no condition holds, listing the source of every
+/// condition that was checked.
+///
+/// No default arm is accepted. As with [`cond_expect!`](crate::cond_expect), the panic reports
+/// the location of the invocation and is kept off the likely path.
+///
+/// ```should_panic
+/// # use cond::cond_unreachable;
+/// let (width, height) = (0, 3);
+/// // panics with:
+/// // no condition held; checked:
+/// //     width > height
+/// //     width < height && width > 0
+/// //     width == height
+/// let shape = cond_unreachable! {
+///     width > height => "landscape",
+///     width < height && width > 0 => "portrait",
+///     width == height => "square",
+/// };
+/// ```
+macro_rules! cond_unreachable {
+    ($($condition:expr => $value:expr),+ $(,)?) => {
+        $(if $condition { $value } else)+ {
+            $crate::__private::no_condition_held(&[$(::core::stringify!($condition)),+])
+        }
+    };
+}
+
+/// Panics with the list of `conditions` that were checked, reporting the caller's location.
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn no_condition_held(conditions: &[&str]) -> ! {
+    struct Conditions<'a>(&'a [&'a str]);
+
+    impl core::fmt::Display for Conditions<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            for condition in self.0 {
+                write!(f, "\n    {}", condition)?;
+            }
+            Ok(())
+        }
+    }
+
+    panic!("no condition held; checked:{}", Conditions(conditions))
+}
+
+/// Panics with `message`, reporting the caller's location.
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn no_match(message: &str) -> ! {
+    panic!("{}", message)
+}
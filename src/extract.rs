@@ -0,0 +1,83 @@
+//! Selection of the first extractor that succeeds, binding what it extracted.
+
+#[macro_export]
+/// Tries a sequence of extractors in order, and evaluates the body of the first one that
+/// succeeds, with the extracted value bound to a pattern.
+///
+/// Each arm is `pattern = extractor => body`, where the extractor evaluates to an [`Option`] or a
+/// [`Result`], and succeeds when it is `Some` or `Ok`. Errors are discarded. Later extractors are
+/// not evaluated once one succeeds. A default arm, `_ => body`, is evaluated when none succeeds,
+/// and is required unless the bodies evaluate to `()`.
+///
+/// ```
+/// # use cond::extract;
+/// #[derive(Debug, PartialEq)]
+/// enum Port { Number(u16), Named(&'static str), Unknown }
+///
+/// fn named(s: &str) -> Option<&'static str> {
+///     ["http", "https"].into_iter().find(|&name| name == s)
+/// }
+///
+/// let port = |s: &str| extract! {
+///     n = s.parse::<u16>() => Port::Number(n),
+///     name = named(s) => Port::Named(name),
+///     (n, _) = s.split_once('/').ok_or(()).map(|(n, p)| (n.parse::<u16>(), p)) => match n {
+///         Ok(n) => Port::Number(n),
+///         Err(_) => Port::Unknown,
+///     },
+///     _ => Port::Unknown,
+/// };
+///
+/// assert_eq!(port("8080"), Port::Number(8080));
+/// assert_eq!(port("https"), Port::Named("https"));
+/// assert_eq!(port("53/udp"), Port::Number(53));
+/// assert_eq!(port("gopher"), Port::Unknown);
+/// ```
+macro_rules! extract {
+    (@chain [$($chain:tt)*] _ => $default:expr $(,)?) => {
+        $($chain)* { $default }
+    };
+    (@chain [$($chain:tt)*] $(,)?) => {
+        $($chain)* {}
+    };
+    (
+        @chain [$($chain:tt)*]
+        $binding:pat = $extractor:expr => $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::extract!(@chain [
+            $($chain)*
+            if let ::core::option::Option::Some($binding) =
+                $crate::__private::Extract::extracted($extractor)
+            {
+                $body
+            } else
+        ] $($($rest)*)?)
+    };
+    ($($arms:tt)*) => {
+        $crate::extract!(@chain [] $($arms)*)
+    };
+}
+
+/// Turns the results of [`extract!`](crate::extract) extractors into [`Option`]s.
+#[doc(hidden)]
+pub trait Extract {
+    type Value;
+
+    fn extracted(self) -> Option<Self::Value>;
+}
+
+impl<T> Extract for Option<T> {
+    type Value = T;
+
+    fn extracted(self) -> Option<T> {
+        self
+    }
+}
+
+impl<T, E> Extract for Result<T, E> {
+    type Value = T;
+
+    fn extracted(self) -> Option<T> {
+        self.ok()
+    }
+}
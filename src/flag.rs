@@ -0,0 +1,118 @@
+//! Atomic flags for dispatching on events raised in interrupt handlers or other threads.
+//!
+//! A handler sets a flag; the main loop tests or takes it as a condition:
+//!
+//! ```
+//! # use cond::cond;
+//! use cond::flag::{FlagBits, FlagCond};
+//!
+//! static TIMER: FlagCond = FlagCond::new();
+//! static UART: FlagBits = FlagBits::new();
+//! const RX: u32 = 1 << 0;
+//! const TX_DONE: u32 = 1 << 1;
+//!
+//! // In the interrupt handlers:
+//! TIMER.set();
+//! UART.set(RX);
+//!
+//! // In the main loop:
+//! let mut handled = Vec::new();
+//! loop {
+//!     cond! {
+//!         UART.take(RX) => handled.push("rx"),
+//!         UART.take(TX_DONE) => handled.push("tx done"),
+//!         TIMER.take() => handled.push("tick"),
+//!         _ => break,
+//!     }
+//! }
+//! assert_eq!(handled, ["rx", "tick"]);
+//! ```
+//!
+//! # Ordering
+//!
+//! Setting a flag is a release operation, and testing or taking it is an acquire operation, so
+//! everything the setter wrote before setting the flag is visible to code that observes the flag
+//! as set. Clearing a flag is relaxed.
+//!
+//! Taking a flag, and setting or clearing bits of a [`FlagBits`], need atomic read-modify-write
+//! instructions, so on targets with only atomic loads and stores (such as `thumbv6m`) those
+//! operations are not available.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// A single event flag.
+#[derive(Debug, Default)]
+pub struct FlagCond(AtomicBool);
+
+impl FlagCond {
+    /// Creates a cleared flag.
+    pub const fn new() -> Self {
+        FlagCond(AtomicBool::new(false))
+    }
+
+    /// Sets the flag.
+    pub fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Clears the flag.
+    pub fn clear(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns whether the flag is set, leaving it unchanged.
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Clears the flag and returns whether it was set, in one atomic step, so that an event is
+    /// handled exactly once.
+    #[cfg(target_has_atomic = "8")]
+    pub fn take(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// A word of up to 32 event flags, addressed by bit masks.
+#[derive(Debug, Default)]
+pub struct FlagBits(AtomicU32);
+
+impl FlagBits {
+    /// Creates a word with every flag cleared.
+    pub const fn new() -> Self {
+        FlagBits(AtomicU32::new(0))
+    }
+
+    /// Sets the flags in `mask`.
+    #[cfg(target_has_atomic = "32")]
+    pub fn set(&self, mask: u32) {
+        self.0.fetch_or(mask, Ordering::Release);
+    }
+
+    /// Clears the flags in `mask`.
+    #[cfg(target_has_atomic = "32")]
+    pub fn clear(&self, mask: u32) {
+        self.0.fetch_and(!mask, Ordering::Relaxed);
+    }
+
+    /// Returns whether any flag in `mask` is set, leaving them unchanged.
+    pub fn any(&self, mask: u32) -> bool {
+        self.0.load(Ordering::Acquire) & mask != 0
+    }
+
+    /// Returns whether every flag in `mask` is set, leaving them unchanged.
+    pub fn all(&self, mask: u32) -> bool {
+        self.0.load(Ordering::Acquire) & mask == mask
+    }
+
+    /// Clears the flags in `mask` and returns whether any of them was set, in one atomic step.
+    #[cfg(target_has_atomic = "32")]
+    pub fn take(&self, mask: u32) -> bool {
+        self.0.fetch_and(!mask, Ordering::AcqRel) & mask != 0
+    }
+
+    /// Returns every flag that is set.
+    pub fn get(&self) -> u32 {
+        self.0.load(Ordering::Acquire)
+    }
+}
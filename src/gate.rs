@@ -0,0 +1,468 @@
+//! Stateful conditions, for tables evaluated repeatedly, such as in a control or game loop.
+//!
+//! Each type here keeps a little state between evaluations and turns it into a `bool` that can be
+//! used as a condition.
+//!
+//! ```
+//! # use cond::cond;
+//! let mut overheated = cond::hysteresis!(> 80.0, < 75.0);
+//! let mut fan = Vec::new();
+//! for temp in [70.0, 82.0, 78.0, 74.0] {
+//!     fan.push(cond! {
+//!         overheated.update(temp) => "on",
+//!         _ => "off",
+//!     });
+//! }
+//! assert_eq!(fan, ["off", "on", "on", "off"]);
+//! ```
+
+use core::cmp::Ordering;
+use core::time::Duration;
+
+use crate::clock::Clock;
+
+/// A threshold condition with separate thresholds for turning on and off, so that a value hovering
+/// around one threshold does not make it chatter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hysteresis<T> {
+    on: T,
+    off: T,
+    rising: bool,
+    state: bool,
+}
+
+impl<T: PartialOrd> Hysteresis<T> {
+    /// Creates a condition that turns on when the value rises above `on` and turns off when it
+    /// falls below `off`. It starts off.
+    pub fn above(on: T, off: T) -> Self {
+        debug_assert!(off <= on, "the off threshold must not be above the on threshold");
+        Hysteresis {
+            on,
+            off,
+            rising: true,
+            state: false,
+        }
+    }
+
+    /// Creates a condition that turns on when the value falls below `on` and turns off when it
+    /// rises above `off`. It starts off.
+    pub fn below(on: T, off: T) -> Self {
+        debug_assert!(off >= on, "the off threshold must not be below the on threshold");
+        Hysteresis {
+            on,
+            off,
+            rising: false,
+            state: false,
+        }
+    }
+
+    /// Feeds a new value and returns whether the condition is now on.
+    pub fn update(&mut self, value: T) -> bool {
+        self.state = match (self.state, self.rising) {
+            (false, true) => value > self.on,
+            // Stay on unless the value is definitely past the off threshold.
+            (true, true) => value.partial_cmp(&self.off) != Some(Ordering::Less),
+            (false, false) => value < self.on,
+            (true, false) => value.partial_cmp(&self.off) != Some(Ordering::Greater),
+        };
+        self.state
+    }
+
+    /// Returns whether the condition is on, without feeding a value.
+    pub fn is_on(&self) -> bool {
+        self.state
+    }
+}
+
+#[macro_export]
+/// Builds a [`Hysteresis`](crate::gate::Hysteresis) from its two thresholds.
+///
+/// `hysteresis!(> on, < off)` turns on above `on` and off below `off`;
+/// `hysteresis!(< on, > off)` turns on below `on` and off above `off`.
+///
+/// ```
+/// let mut low_battery = cond::hysteresis!(< 10, > 15);
+/// assert!(low_battery.update(8));
+/// assert!(low_battery.update(12));
+/// assert!(!low_battery.update(16));
+/// ```
+macro_rules! hysteresis {
+    (> $on:expr, < $off:expr $(,)?) => {
+        $crate::gate::Hysteresis::above($on, $off)
+    };
+    (< $on:expr, > $off:expr $(,)?) => {
+        $crate::gate::Hysteresis::below($on, $off)
+    };
+}
+
+/// A condition that only changes after the raw condition has disagreed with it for a number of
+/// consecutive evaluations.
+///
+/// ```
+/// use cond::gate::Debounce;
+///
+/// let mut pressed = Debounce::new(3);
+/// let samples = [true, false, true, true, true, false];
+/// let output: Vec<bool> = samples.iter().map(|&raw| pressed.update(raw)).collect();
+/// assert_eq!(output, [false, false, false, false, true, true]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Debounce {
+    required: u32,
+    streak: u32,
+    state: bool,
+}
+
+impl Debounce {
+    /// Creates a condition that flips after `required` consecutive disagreeing evaluations. It
+    /// starts off. A `required` of 0 or 1 follows the raw condition immediately.
+    pub fn new(required: u32) -> Self {
+        Debounce {
+            required,
+            streak: 0,
+            state: false,
+        }
+    }
+
+    /// Feeds the raw condition and returns the debounced one.
+    pub fn update(&mut self, raw: bool) -> bool {
+        if raw == self.state {
+            self.streak = 0;
+        } else {
+            self.streak += 1;
+            if self.streak >= self.required {
+                self.state = raw;
+                self.streak = 0;
+            }
+        }
+        self.state
+    }
+
+    /// Returns the debounced condition, without feeding a value.
+    pub fn is_on(&self) -> bool {
+        self.state
+    }
+}
+
+/// A condition that opens, turning off, after repeated failures, and lets a trial call through once
+/// a cooldown has passed.
+///
+/// Guard an arm with [`allow`](CircuitBreaker::allow) and pass the arm's result through
+/// [`record`](CircuitBreaker::record), so a failing backend is skipped in favour of later arms
+/// until it has had time to recover:
+///
+/// ```
+/// # use cond::cond;
+/// use cond::clock::ManualClock;
+/// use cond::gate::CircuitBreaker;
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut primary = CircuitBreaker::new(&clock, 2, Duration::from_secs(10), Duration::from_secs(30));
+/// let fetch_primary = || Err::<&str, _>("timeout");
+///
+/// let mut fetch = || cond! {
+///     primary.allow() => primary.record(fetch_primary()).or(Ok("secondary")),
+///     _ => Ok::<_, &str>("cached"),
+/// };
+/// assert_eq!(fetch(), Ok("secondary"));
+/// assert_eq!(fetch(), Ok("secondary"));
+/// // Two failures within ten seconds opened the circuit.
+/// assert_eq!(fetch(), Ok("cached"));
+/// clock.advance(Duration::from_secs(30));
+/// // The cooldown has passed, so the primary gets another try.
+/// assert_eq!(fetch(), Ok("secondary"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker<C> {
+    clock: C,
+    threshold: u32,
+    window: Duration,
+    cooldown: Duration,
+    failures: u32,
+    window_start: Duration,
+    state: Circuit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Circuit {
+    Closed,
+    Open { since: Duration },
+    HalfOpen { since: Duration },
+}
+
+impl<C: Clock> CircuitBreaker<C> {
+    /// Creates a closed breaker that opens after `threshold` failures within `window` of the
+    /// first one, and stays open for `cooldown`.
+    pub fn new(clock: C, threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            clock,
+            threshold,
+            window,
+            cooldown,
+            failures: 0,
+            window_start: Duration::ZERO,
+            state: Circuit::Closed,
+        }
+    }
+
+    /// Returns whether a call may go through. Once the cooldown has passed, the breaker is
+    /// half-open: one trial call is allowed, and its outcome closes or reopens the circuit. A trial
+    /// whose outcome is never recorded, because the arm was not taken after all or returned early,
+    /// expires after another cooldown, and the next call is allowed as a new trial.
+    pub fn allow(&mut self) -> bool {
+        let now = self.clock.now();
+        match self.state {
+            Circuit::Closed => true,
+            Circuit::Open { since } | Circuit::HalfOpen { since }
+                if now.saturating_sub(since) >= self.cooldown =>
+            {
+                self.state = Circuit::HalfOpen { since: now };
+                true
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => false,
+        }
+    }
+
+    /// Records the outcome of a call and returns it unchanged.
+    pub fn record<T, E>(&mut self, result: Result<T, E>) -> Result<T, E> {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(_) => self.record_failure(),
+        }
+        result
+    }
+
+    /// Records a successful call, closing the circuit if it was half-open.
+    pub fn record_success(&mut self) {
+        if let Circuit::HalfOpen { .. } = self.state {
+            self.state = Circuit::Closed;
+            self.failures = 0;
+        }
+    }
+
+    /// Records a failed call, opening the circuit if the threshold is reached or the trial call of
+    /// a half-open circuit failed.
+    pub fn record_failure(&mut self) {
+        let now = self.clock.now();
+        if let Circuit::HalfOpen { .. } = self.state {
+            self.state = Circuit::Open { since: now };
+            return;
+        }
+        if self.failures == 0 || now.saturating_sub(self.window_start) > self.window {
+            self.failures = 0;
+            self.window_start = now;
+        }
+        self.failures += 1;
+        if self.failures >= self.threshold {
+            self.state = Circuit::Open { since: now };
+            self.failures = 0;
+        }
+    }
+
+    /// Returns whether the circuit is open, so calls are being skipped.
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, Circuit::Open { .. })
+    }
+}
+
+/// A condition that holds at most `N` times in any sliding window of a given length.
+///
+/// Put it last in an arm's condition, so it only counts the times the rest of the condition held,
+/// and let later arms handle the suppressed cases:
+///
+/// ```
+/// # use cond::cond;
+/// use cond::clock::ManualClock;
+/// use cond::gate::RateLimit;
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut pages = RateLimit::<_, 2>::new(&clock, Duration::from_secs(60));
+/// let mut sent = Vec::new();
+/// for _ in 0..3 {
+///     let disk_full = true;
+///     sent.push(cond! {
+///         disk_full && pages.allow() => "page",
+///         disk_full => "log",
+///         _ => "nothing",
+///     });
+///     clock.advance(Duration::from_secs(20));
+/// }
+/// assert_eq!(sent, ["page", "page", "log"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimit<C, const N: usize> {
+    clock: C,
+    window: Duration,
+    fired: [Duration; N],
+    len: usize,
+    next: usize,
+}
+
+impl<C: Clock, const N: usize> RateLimit<C, N> {
+    /// Creates a limit of `N` per `window`.
+    pub fn new(clock: C, window: Duration) -> Self {
+        RateLimit {
+            clock,
+            window,
+            fired: [Duration::ZERO; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Returns whether fewer than `N` calls have been allowed within the last window, and if so
+    /// counts this call.
+    pub fn allow(&mut self) -> bool {
+        if N == 0 {
+            return false;
+        }
+        let now = self.clock.now();
+        // `next` is the oldest entry once the buffer is full.
+        if self.len == N && now.saturating_sub(self.fired[self.next]) < self.window {
+            return false;
+        }
+        self.fired[self.next] = now;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+        true
+    }
+
+    /// Returns how many more calls would be allowed right now.
+    pub fn remaining(&self) -> usize {
+        let now = self.clock.now();
+        let recent = self.fired[..self.len]
+            .iter()
+            .filter(|&&fired| now.saturating_sub(fired) < self.window)
+            .count();
+        N - recent
+    }
+}
+
+/// A condition that, once it has held, does not hold again until a cooldown has passed.
+///
+/// Put it last in an arm's condition, so the cooldown only starts when the arm is taken:
+///
+/// ```
+/// # use cond::cond;
+/// use cond::clock::ManualClock;
+/// use cond::gate::Cooldown;
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut fireball = Cooldown::new(&clock, Duration::from_secs(5));
+/// let mut actions = Vec::new();
+/// for _ in 0..4 {
+///     let enemy_in_range = true;
+///     actions.push(cond! {
+///         enemy_in_range && fireball.allow() => "fireball",
+///         enemy_in_range => "melee",
+///         _ => "patrol",
+///     });
+///     clock.advance(Duration::from_secs(2));
+/// }
+/// assert_eq!(actions, ["fireball", "melee", "melee", "fireball"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cooldown<C> {
+    clock: C,
+    cooldown: Duration,
+    fired: Option<Duration>,
+}
+
+impl<C: Clock> Cooldown<C> {
+    /// Creates a cooldown of `cooldown`. It starts ready.
+    pub fn new(clock: C, cooldown: Duration) -> Self {
+        Cooldown {
+            clock,
+            cooldown,
+            fired: None,
+        }
+    }
+
+    /// Returns whether the cooldown has passed, and if so starts it again.
+    pub fn allow(&mut self) -> bool {
+        let ready = self.is_ready();
+        if ready {
+            self.fired = Some(self.clock.now());
+        }
+        ready
+    }
+
+    /// Returns whether the cooldown has passed, without starting it.
+    pub fn is_ready(&self) -> bool {
+        self.remaining() == Duration::ZERO
+    }
+
+    /// Returns how long until the cooldown has passed.
+    pub fn remaining(&self) -> Duration {
+        match self.fired {
+            Some(fired) => self.cooldown.saturating_sub(self.clock.now().saturating_sub(fired)),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Makes the condition ready again immediately.
+    pub fn reset(&mut self) {
+        self.fired = None;
+    }
+}
+
+/// Like [`Cooldown`], but counting ticks of a loop instead of reading a clock.
+///
+/// ```
+/// use cond::gate::TickCooldown;
+///
+/// let mut dash = TickCooldown::new(3);
+/// let mut dashes = Vec::new();
+/// for _ in 0..7 {
+///     dashes.push(dash.allow());
+///     dash.tick();
+/// }
+/// assert_eq!(dashes, [true, false, false, true, false, false, true]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickCooldown {
+    ticks: u32,
+    remaining: u32,
+}
+
+impl TickCooldown {
+    /// Creates a cooldown of `ticks` ticks. It starts ready.
+    pub const fn new(ticks: u32) -> Self {
+        TickCooldown {
+            ticks,
+            remaining: 0,
+        }
+    }
+
+    /// Returns whether the cooldown has passed, and if so starts it again.
+    pub fn allow(&mut self) -> bool {
+        let ready = self.is_ready();
+        if ready {
+            self.remaining = self.ticks;
+        }
+        ready
+    }
+
+    /// Advances the cooldown by one tick. Call it once per iteration of the loop.
+    pub fn tick(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Returns whether the cooldown has passed, without starting it.
+    pub fn is_ready(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Returns how many ticks until the cooldown has passed.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Makes the condition ready again immediately.
+    pub fn reset(&mut self) {
+        self.remaining = 0;
+    }
+}
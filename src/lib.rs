@@ -5,6 +5,135 @@
 //! Disable tests in `no_std` environments.
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+pub mod audit;
+#[macro_use]
+mod batch;
+#[macro_use]
+pub mod behavior;
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "macros")]
+pub mod checked;
+#[cfg(feature = "macros")]
+mod classify;
+pub mod clock;
+#[cfg(feature = "std")]
+#[macro_use]
+mod debug;
+#[macro_use]
+mod default;
+#[cfg(feature = "defmt")]
+pub mod defmt_log;
+#[macro_use]
+pub mod dispatch;
+#[cfg(feature = "macros")]
+mod env;
+#[macro_use]
+mod error;
+#[macro_use]
+mod expect;
+#[macro_use]
+mod extract;
+pub mod flag;
+#[macro_use]
+pub mod gate;
+#[macro_use]
+mod named;
+#[cfg(feature = "otel")]
+pub mod otel;
+#[macro_use]
+mod parse;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "rand")]
+#[macro_use]
+mod random;
+#[cfg(feature = "alloc")]
+#[macro_use]
+mod range;
+#[macro_use]
+mod reduce;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[macro_use]
+mod repeat;
+pub mod sample;
+pub mod schedule;
+#[macro_use]
+mod score;
+#[macro_use]
+mod snapshot;
+#[cfg(feature = "macros")]
+mod sql;
+#[cfg(feature = "std")]
+#[macro_use]
+mod specialize;
+#[macro_use]
+mod statics;
+#[macro_use]
+mod target;
+#[cfg(feature = "testkit")]
+#[macro_use]
+pub mod testkit;
+#[macro_use]
+pub mod toggle;
+#[macro_use]
+mod unify;
+#[cfg(feature = "macros")]
+mod version;
+#[macro_use]
+mod with;
+
+#[cfg(feature = "macros")]
+pub use classify::Classify;
+pub use error::NoMatchError;
+#[cfg(feature = "alloc")]
+pub use range::{RangeCond, RangeError};
+#[cfg(feature = "alloc")]
+pub use score::Scorer;
+pub use statics::{StaticArm, StaticCond};
+#[cfg(feature = "macros")]
+pub use env::cond_env;
+#[cfg(feature = "macros")]
+pub use sql::cond_sql;
+#[cfg(feature = "macros")]
+pub use unify::cond_enum;
+#[cfg(feature = "macros")]
+pub use version::cond_version;
+
+/// Items used by the macros' expansions. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "std")]
+    pub use crate::debug::{dbg_condition, dbg_selected};
+    pub use crate::expect::{no_condition_held, no_match};
+    pub use crate::extract::Extract;
+    #[cfg(feature = "alloc")]
+    pub use alloc::{boxed::Box, format, vec};
+    #[cfg(feature = "either")]
+    pub use either::Either;
+    #[cfg(feature = "registry")]
+    pub use inventory;
+    #[cfg(feature = "rand")]
+    pub use crate::random::{uniform_index, weighted_index};
+    #[cfg(feature = "alloc")]
+    pub use crate::range::RangeBounds;
+    pub use crate::score::best_index;
+    pub use crate::target::is_target_feature;
+    #[cfg(feature = "std")]
+    pub use std::sync::OnceLock;
+    #[cfg(all(feature = "std", target_arch = "aarch64"))]
+    pub use std::arch::is_aarch64_feature_detected;
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    pub use std::arch::is_x86_feature_detected;
+}
+
 #[macro_export]
 /// A macro for matching on boolean conditions, like an empty [Go `switch` statement].
 ///
@@ -35,6 +164,79 @@
 /// assert_eq!(text, "a is less than b");
 /// ```
 ///
+/// The default arm can bind the source of every condition, all of which were checked and found
+/// false, by writing a name in parentheses after the `_`:
+///
+/// ```
+/// # use cond::cond;
+/// let (cached, online) = (false, false);
+/// let source = cond! {
+///     cached => "cache",
+///     online => "network",
+///     _ (failed) => {
+///         assert_eq!(failed, ["cached", "online"]);
+///         "none"
+///     }
+/// };
+/// assert_eq!(source, "none");
+/// ```
+///
+/// A table with nested arms, described below, cannot bind the failed conditions, since its
+/// default is also used when no condition of a nested table holds:
+///
+/// ```compile_fail
+/// # use cond::cond;
+/// # let (is_guest, is_public, is_admin) = (true, false, false);
+/// let access = cond! {
+///     is_guest =>> {
+///         is_public => "read",
+///     },
+///     is_admin => "write",
+///     _ (failed) => "deny",
+/// };
+/// ```
+///
+/// An arm can also open a nested table with `=>>` followed by the nested arms in braces. The
+/// nested table is only evaluated when its arm's condition holds, and if none of its own
+/// conditions hold either, the outer default is used, without checking the later outer arms.
+///
+/// ```
+/// # use cond::cond;
+/// # let (is_admin, is_owner, is_public, is_guest) = (false, false, true, true);
+/// let access = cond! {
+///     is_admin => "write",
+///     is_guest =>> {
+///         is_public => "read",
+///         is_owner => "request",
+///     },
+///     _ => "deny",
+/// };
+/// assert_eq!(access, "read");
+/// ```
+///
+/// The outer default is copied into every nested table that has no default of its own, so a
+/// long default is better written as a call to a function.
+///
+/// An arm written with `@unless` before its condition matches when the condition is false, which
+/// reads better than a negation in front of a long condition. `@unless` arms can be mixed with
+/// other arms, including in nested tables. The `@` keeps conditions that start with a variable
+/// named `unless` working.
+///
+/// ```
+/// # use cond::cond;
+/// # let (is_ready, is_cancelled) = (|| false, false);
+/// let state = cond! {
+///     is_cancelled => "stop",
+///     @unless is_ready() => "wait",
+///     _ => "run",
+/// };
+/// assert_eq!(state, "wait");
+/// ```
+///
+/// The expansion is a chain of `if` expressions, with nested tables as chains inside them, and no
+/// labeled blocks, closures or helper functions, so arms can use `return`, `break`, `continue`, `?`
+/// and `.await` as in the surrounding code, and `yield` inside coroutines on nightly Rust.
+///
 /// # Caveat
 ///
 /// Expressions that end with blocks must still have commas after them in `cond` invocations, unlike
@@ -73,11 +275,96 @@
 ///
 /// [Go `switch` statement]: <https://go.dev/ref/spec#Switch_statements>
 macro_rules! cond {
+    // Each arm is parsed into `[condition] kind payload`, with `@unless` arms negated. The first
+    // tokens in brackets are the block to fall back to when no condition holds and the table has
+    // no default: nothing for a table, and the enclosing default for a nested table, which is
+    // repeated there instead of breaking out of a labeled block, so that unlabeled `break` and
+    // `continue` still work in every arm.
+    (@parse $fallback:tt [$($arms:tt)*] $(,)?) => {
+        $crate::cond! { @emit [$($arms)*] $fallback }
+    };
+    (@parse $fallback:tt [$($arms:tt)*] _ => $default:expr $(,)?) => {
+        $crate::cond! { @emit [$($arms)*] { $default } }
+    };
+    (
+        @parse $fallback:tt [$([$($condition:tt)*] $kind:ident $payload:tt)*]
+        _ ($failed:ident) => $default:expr $(,)?
+    ) => {
+        $(if $($condition)* {
+            $crate::cond! { @failed_arm $kind $payload }
+        } else)* {
+            let $failed: &[&str] = &[$(::core::stringify!($($condition)*)),*];
+            $default
+        }
+    };
+    // A nested table falling back to this default would bind the outer conditions, including the
+    // one that held, instead of the nested ones that were found false.
+    (@failed_arm value [$value:expr]) => {
+        $value
+    };
+    (@failed_arm nested $nested:tt) => {
+        ::core::compile_error!("a default arm binding the failed conditions cannot be used in a table with `=>>` arms")
+    };
+    // Allows `cond! { , _ => default }`, which the table syntax has always accepted.
+    (@parse $fallback:tt [] , _ $($default:tt)*) => {
+        $crate::cond! { @parse $fallback [] _ $($default)* }
+    };
+    (
+        @parse $fallback:tt [$($arms:tt)*]
+        @unless $condition:expr =>> { $($nested:tt)* } $(, $($rest:tt)*)?
+    ) => {
+        $crate::cond! {
+            @parse $fallback [$($arms)* [!($condition)] nested { $($nested)* }] $($($rest)*)?
+        }
+    };
+    (
+        @parse $fallback:tt [$($arms:tt)*]
+        @unless $condition:expr => $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cond! { @parse $fallback [$($arms)* [!($condition)] value [$value]] $($($rest)*)? }
+    };
+    (
+        @parse $fallback:tt [$($arms:tt)*]
+        $condition:expr =>> { $($nested:tt)* } $(, $($rest:tt)*)?
+    ) => {
+        $crate::cond! {
+            @parse $fallback [$($arms)* [$condition] nested { $($nested)* }] $($($rest)*)?
+        }
+    };
+    (
+        @parse $fallback:tt [$($arms:tt)*]
+        $condition:expr => $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cond! { @parse $fallback [$($arms)* [$condition] value [$value]] $($($rest)*)? }
+    };
+    (@emit [$([$($condition:tt)*] $kind:ident $payload:tt)*] $default:tt) => {
+        $(if $($condition)* {
+            $crate::cond! { @arm $default $kind $payload }
+        } else)* $default
+    };
+    (@arm $default:tt value [$value:expr]) => {
+        $value
+    };
+    (@arm $default:tt nested { $($nested:tt)* }) => {
+        $crate::cond! { @parse $default [] $($nested)* }
+    };
+    // Flat tables are expanded in one step, whatever their length.
     ($($condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?) => {
         $(if $condition { $value } else)*
         { $($default)? }
     };
+    ($($condition:expr => $value:expr,)* _ ($failed:ident) => $default:expr $(,)?) => {
+        $(if $condition { $value } else)*
+        {
+            let $failed: &[&str] = &[$(::core::stringify!($condition)),*];
+            $default
+        }
+    };
+    ($($arms:tt)*) => {
+        $crate::cond! { @parse {} [] $($arms)* }
+    };
 }
 
 #[cfg(test)]
+#[allow(unused_imports, clippy::bool_assert_comparison)]
 mod tests;
@@ -0,0 +1,146 @@
+//! Tables whose arms have names.
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but with a name for the table and for each arm.
+///
+/// The first argument is the table name, followed by a semicolon, and each arm is written
+/// `"name": condition => value`. The names do not change how the table is evaluated. With the
+/// `registry` feature, the table and its arm names are added to the
+/// [`registry`](crate::registry), where tooling can enumerate them; arm names should therefore be
+/// unique within a table, and table names unique within a program. With the `testkit` feature,
+/// tests can force an arm by name with [`testkit::force`](crate::testkit::force). With the
+/// `otel` feature, each evaluation is traced as an OpenTelemetry span; see [`otel`](crate::otel).
+/// With the `defmt` feature, the selected arm is logged with `defmt`; see
+/// [`defmt_log`](crate::defmt_log).
+///
+/// ```
+/// # use cond::cond_named;
+/// let (cached, stale) = (true, true);
+/// let source = cond_named! { "config.source";
+///     "fresh_cache": cached && !stale => "cache",
+///     "refresh": cached => "network, then cache",
+///     _ => "network",
+/// };
+/// assert_eq!(source, "network, then cache");
+/// ```
+macro_rules! cond_named {
+    (
+        $site:literal;
+        $($name:literal : $condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?
+    ) => {{
+        $crate::__register_site!($site, [$($name),*]);
+        match $crate::__forced_arm!($site, [$($name),*]) {
+            ::core::option::Option::Some(chosen) => {
+                let mut arm = 0;
+                $(if { arm += 1; chosen == ::core::option::Option::Some(arm - 1) } { $value } else)*
+                { $($default)? }
+            }
+            ::core::option::Option::None => {
+                $crate::__traced_arms!(
+                    $site, [$($name),*], [$($condition => $value),*], [$($default)?]
+                )
+            }
+        }
+    }};
+}
+
+#[cfg(feature = "registry")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_site {
+    ($site:literal, [$($name:literal),*]) => {
+        $crate::__private::inventory::submit! {
+            $crate::registry::Site {
+                name: $site,
+                arms: &[$($name),*],
+                file: ::core::file!(),
+                line: ::core::line!(),
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "registry"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_site {
+    ($site:literal, [$($name:literal),*]) => {};
+}
+
+#[cfg(feature = "testkit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forced_arm {
+    ($site:literal, [$($name:literal),*]) => {{
+        // Checked in the invoking crate, so that its release builds skip the lookup.
+        #[cfg(any(test, debug_assertions))]
+        let forced = $crate::testkit::forced_arm($site, &[$($name),*]);
+        #[cfg(not(any(test, debug_assertions)))]
+        let forced = ::core::option::Option::None::<::core::option::Option<usize>>;
+        forced
+    }};
+}
+
+#[cfg(not(feature = "testkit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forced_arm {
+    ($site:literal, [$($name:literal),*]) => {
+        ::core::option::Option::None::<::core::option::Option<usize>>
+    };
+}
+
+#[cfg(feature = "otel")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __traced_arms {
+    (
+        $site:literal, [$($name:literal),*],
+        [$($condition:expr => $value:expr),*], [$($default:expr)?]
+    ) => {{
+        let mut evaluation = $crate::otel::Evaluation::start($site, &[$($name),*]);
+        $(if evaluation.check($condition) {
+            evaluation.matched();
+            $crate::__log_arm!($site, $name);
+            $value
+        } else)* {
+            evaluation.unmatched();
+            $crate::__log_arm!($site, "_");
+            $($default)?
+        }
+    }};
+}
+
+#[cfg(not(feature = "otel"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __traced_arms {
+    (
+        $site:literal, [$($name:literal),*],
+        [$($condition:expr => $value:expr),*], [$($default:expr)?]
+    ) => {
+        $(if $condition {
+            $crate::__log_arm!($site, $name);
+            $value
+        } else)* {
+            $crate::__log_arm!($site, "_");
+            $($default)?
+        }
+    };
+}
+
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_arm {
+    ($site:literal, $arm:literal) => {
+        $crate::defmt_log::matched(::defmt::intern!($site), ::defmt::intern!($arm))
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_arm {
+    ($site:literal, $arm:literal) => {};
+}
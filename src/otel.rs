@@ -0,0 +1,101 @@
+//! OpenTelemetry spans for the decisions of [`cond_named!`](crate::cond_named) tables.
+//!
+//! With the `otel` feature, every evaluation of a named table is wrapped in a span, named after
+//! the table and created with the global tracer provider's tracer named [`TRACER_NAME`]. The span
+//! covers the evaluation of the conditions, and ends when an arm is selected, before its value is
+//! evaluated. It carries these attributes:
+//!
+//! - `cond.site`: the name of the table.
+//! - `cond.arm`: the name of the selected arm, or `_` for the default arm or when no arm matched.
+//! - `cond.conditions_evaluated`: how many conditions were evaluated.
+//! - `cond.evaluation_ns`: how long evaluating the conditions took, in nanoseconds.
+//!
+//! Arms forced with [`testkit::force`](crate::testkit::force) evaluate no conditions and are not
+//! traced.
+//!
+//! Which evaluations are traced is decided by [`SAMPLER`], after an arm is selected, so the span
+//! is only created for the evaluations that are traced:
+//!
+//! ```
+//! use cond::otel::SAMPLER;
+//! use cond::sample::Rate;
+//!
+//! SAMPLER.set_default(Rate::one_in(1000));
+//! SAMPLER.set("auth.decision", Rate::ALL.non_default_only());
+//! ```
+
+use opentelemetry::global;
+use opentelemetry::trace::{Span, Tracer};
+use opentelemetry::KeyValue;
+use std::time::{Instant, SystemTime};
+
+use crate::sample::Sampler;
+
+/// The name of the tracer the spans are created with.
+pub const TRACER_NAME: &str = "cond";
+
+/// The sampler deciding which evaluations are traced, keyed by table name. Initially, every
+/// evaluation is traced.
+pub static SAMPLER: Sampler = Sampler::new();
+
+/// An evaluation of a named table in progress.
+#[doc(hidden)]
+pub struct Evaluation {
+    site: &'static str,
+    arms: &'static [&'static str],
+    evaluated: usize,
+    started: SystemTime,
+    start: Instant,
+}
+
+impl Evaluation {
+    pub fn start(site: &'static str, arms: &'static [&'static str]) -> Self {
+        Evaluation {
+            site,
+            arms,
+            evaluated: 0,
+            started: SystemTime::now(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Counts a condition, and returns whether it held.
+    pub fn check(&mut self, held: bool) -> bool {
+        self.evaluated += 1;
+        held
+    }
+
+    /// Ends the span for the arm of the last condition checked.
+    pub fn matched(self) {
+        let index = self.evaluated - 1;
+        self.end(Some(index));
+    }
+
+    /// Ends the span for the default arm.
+    pub fn unmatched(self) {
+        self.end(None);
+    }
+
+    fn end(self, index: Option<usize>) {
+        let elapsed = self.start.elapsed().as_nanos();
+        if !SAMPLER.sample(self.site, index) {
+            return;
+        }
+        let arm = index.map_or("_", |index| self.arms[index]);
+        let tracer = global::tracer(TRACER_NAME);
+        let mut span = tracer
+            .span_builder(self.site)
+            .with_start_time(self.started)
+            .start(&tracer);
+        span.set_attributes([
+            KeyValue::new("cond.site", self.site),
+            KeyValue::new("cond.arm", arm),
+            KeyValue::new("cond.conditions_evaluated", self.evaluated as i64),
+            KeyValue::new(
+                "cond.evaluation_ns",
+                i64::try_from(elapsed).unwrap_or(i64::MAX),
+            ),
+        ]);
+        span.end();
+    }
+}
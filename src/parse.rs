@@ -0,0 +1,103 @@
+//! Selection of a parser by the shape of its input.
+
+#[macro_export]
+/// Selects a parser for an input by probing its shape, and evaluates to `Ok` with the result of
+/// the first parser whose probe matches, or `Err` with a [`NoMatchError`](crate::NoMatchError)
+/// listing the probes that were tried.
+///
+/// The first argument is the input, which is sliced with `[..]`, so it can be a `str`, a byte
+/// slice, or anything that derefs to one, followed by a semicolon and the arms. Each arm is
+/// `probe => parser`, where the parser is a function or closure taking the sliced input, and the
+/// probe is one of:
+///
+/// - `starts_with(prefix)` or `ends_with(suffix)`, which call the method of the same name on the
+///   input, so a byte input takes byte strings and a `str` input takes any string pattern;
+/// - any other expression, which is called with the input and must return `bool`.
+///
+/// ```
+/// # use cond::parse_cond;
+/// #[derive(Debug, PartialEq)]
+/// enum Image { Png(usize), Gif(usize), Bmp(usize) }
+///
+/// fn is_bmp(data: &[u8]) -> bool {
+///     data.len() >= 14 && data.starts_with(b"BM")
+/// }
+///
+/// let sniff = |data: &[u8]| parse_cond! { data;
+///     starts_with(b"\x89PNG\r\n\x1a\n") => |data: &[u8]| Image::Png(data.len()),
+///     starts_with(b"GIF87a") || starts_with(b"GIF89a") => |data: &[u8]| Image::Gif(data.len()),
+///     is_bmp => |data: &[u8]| Image::Bmp(data.len()),
+/// };
+///
+/// assert_eq!(sniff(b"GIF89a..."), Ok(Image::Gif(9)));
+/// let error = sniff(b"%PDF-1.7").unwrap_err();
+/// assert_eq!(
+///     error.conditions(),
+///     [
+///         "starts_with(b\"\\x89PNG\\r\\n\\x1a\\n\")",
+///         "starts_with(b\"GIF87a\") || starts_with(b\"GIF89a\")",
+///         "is_bmp",
+///     ],
+/// );
+/// ```
+///
+/// Probes can also combine `starts_with` and `ends_with` with other conditions, such as
+/// `starts_with("{") && ends_with("}")` or `!starts_with(b"\xff\xd8") && data.len() > 4`, as
+/// long as the probe is not a single path or closure, which is always called with the input.
+macro_rules! parse_cond {
+    (@probe $input:ident [$($segment:ident)::+]) => {
+        $($segment)::+($input)
+    };
+    (@probe $input:ident [| $($closure:tt)*]) => {
+        (| $($closure)*)($input)
+    };
+    (@probe $input:ident [$($probe:tt)*]) => {
+        $crate::parse_cond!(@expr $input [] $($probe)*)
+    };
+    (@expr $input:ident [$($out:tt)*]) => {
+        $($out)*
+    };
+    // Method calls are left alone, so `x.starts_with(..)` still refers to `x`.
+    (@expr $input:ident [$($out:tt)*] . $method:ident $($rest:tt)*) => {
+        $crate::parse_cond!(@expr $input [$($out)* . $method] $($rest)*)
+    };
+    (@expr $input:ident [$($out:tt)*] starts_with($($prefix:tt)*) $($rest:tt)*) => {
+        $crate::parse_cond!(@expr $input [$($out)* $input.starts_with($($prefix)*)] $($rest)*)
+    };
+    (@expr $input:ident [$($out:tt)*] ends_with($($suffix:tt)*) $($rest:tt)*) => {
+        $crate::parse_cond!(@expr $input [$($out)* $input.ends_with($($suffix)*)] $($rest)*)
+    };
+    (@expr $input:ident [$($out:tt)*] ($($group:tt)*) $($rest:tt)*) => {
+        $crate::parse_cond!(@expr $input [
+            $($out)* ($crate::parse_cond!(@expr $input [] $($group)*))
+        ] $($rest)*)
+    };
+    (@expr $input:ident [$($out:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::parse_cond!(@expr $input [$($out)* $next] $($rest)*)
+    };
+    (@arms $input:ident [$([$($probe:tt)*] $parser:expr;)*] []) => {
+        $(if $crate::parse_cond!(@probe $input [$($probe)*]) {
+            ::core::result::Result::Ok(($parser)($input))
+        } else)* {
+            ::core::result::Result::Err($crate::NoMatchError::new(
+                ::core::file!(),
+                ::core::line!(),
+                ::core::column!(),
+                &[$(::core::stringify!($($probe)*)),*],
+            ))
+        }
+    };
+    (
+        @arms $input:ident [$($done:tt)*] [$($probe:tt)+]
+        => $parser:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::parse_cond!(@arms $input [$($done)* [$($probe)+] $parser;] [] $($($rest)*)?)
+    };
+    (@arms $input:ident [$($done:tt)*] [$($probe:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::parse_cond!(@arms $input [$($done)*] [$($probe)* $next] $($rest)*)
+    };
+    ($input:expr; $($arms:tt)*) => {{
+        let input = &$input[..];
+        $crate::parse_cond!(@arms input [] [] $($arms)*)
+    }};
+}
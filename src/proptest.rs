@@ -0,0 +1,41 @@
+//! [`proptest`](::proptest) strategies aimed at single arms of a decision table.
+//!
+//! Like the [`testkit`](crate::testkit) helpers, the table is described by a selector: a function
+//! from an input to the index of the arm it picks, with the default arm counted last.
+
+use core::fmt::Debug;
+use std::format;
+
+use ::proptest::strategy::Strategy;
+
+/// Restricts `inputs` to the inputs that select `arm`, so a property can target one branch.
+///
+/// Inputs are generated from `inputs` and rejected until one selects the arm, so `inputs` should
+/// already produce such inputs reasonably often; proptest gives up after too many rejections.
+///
+/// ```
+/// use cond::cond;
+/// use proptest::prelude::*;
+///
+/// fn fee(amount: u32) -> usize {
+///     cond! {
+///         amount < 100 => 0,
+///         amount < 1000 => 1,
+///         _ => 2,
+///     }
+/// }
+///
+/// proptest!(|(amount in cond::proptest::arm(0..2000u32, |a| fee(*a), 1))| {
+///     prop_assert!((100..1000).contains(&amount));
+/// });
+/// ```
+pub fn arm<I, S, F>(inputs: S, select: F, arm: usize) -> impl Strategy<Value = I>
+where
+    I: Debug,
+    S: Strategy<Value = I>,
+    F: Fn(&I) -> usize,
+{
+    inputs.prop_filter(format!("input selecting arm {arm}"), move |input| {
+        select(input) == arm
+    })
+}
@@ -0,0 +1,121 @@
+//! Random selection among arms, behind the `rand` feature.
+
+use rand::Rng;
+
+#[macro_export]
+/// Picks one arm at random, with probability proportional to its weight.
+///
+/// The first argument is a `&mut` reference to a [`rand::Rng`]. Each arm is a weight, optionally
+/// followed by `if` and a condition that must hold for the arm to be eligible. Weights are single
+/// tokens (a literal, a variable, or a parenthesized expression) of any numeric type; arms with a
+/// weight of zero or less are never picked. The default arm is used when no arm is eligible, and,
+/// as with [`cond!`](crate::cond), must be present unless the arms evaluate to `()`.
+///
+/// Every condition is evaluated, each weight only if its condition holds, then only the chosen arm's
+/// value.
+///
+/// ```
+/// # use cond::weighted_choice;
+/// let enemy_near = true;
+/// let stamina = 2;
+/// let action = weighted_choice! { &mut rand::rng();
+///     5 if enemy_near => "attack",
+///     (stamina * 2) if enemy_near => "dodge",
+///     1 => "wander",
+///     _ => "idle",
+/// };
+/// assert!(["attack", "dodge", "wander"].contains(&action));
+/// ```
+macro_rules! weighted_choice {
+    // Arms are collected one at a time, since a `tt` weight would be ambiguous with `_`.
+    (@arms $rng:expr; [$($arm:tt)*]; _ => $default:expr $(,)?) => {
+        $crate::weighted_choice!(@expand $rng; [$($arm)*]; $default)
+    };
+    (@arms $rng:expr; [$($arm:tt)*]; $(,)?) => {
+        $crate::weighted_choice!(@expand $rng; [$($arm)*];)
+    };
+    (@arms $rng:expr; [$($arm:tt)*]; $weight:tt $(if $condition:expr)? => $value:expr $(, $($rest:tt)*)?) => {
+        $crate::weighted_choice!(@arms $rng; [$($arm)* [$weight, (true $(&& $condition)?), $value]]; $($($rest)*)?)
+    };
+
+    (@expand $rng:expr; [$([$weight:tt, $condition:expr, $value:expr])*]; $($default:expr)?) => {{
+        let weights = [$(if $condition { ($weight) as f64 } else { 0.0 }),*];
+        let chosen = $crate::__private::weighted_index($rng, &weights);
+        let mut arm = 0;
+        $(if { arm += 1; arm - 1 == chosen } { $value } else)*
+        { $($default)? }
+    }};
+
+    ($rng:expr; $($arms:tt)*) => {
+        $crate::weighted_choice!(@arms $rng; []; $($arms)*)
+    };
+}
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but picks uniformly at random among all arms whose conditions hold
+/// instead of taking the first.
+///
+/// The first argument is a `&mut` reference to a [`rand::Rng`]. Every condition is evaluated, so
+/// unlike `cond!`, later conditions run even when earlier ones hold. The default arm is used when
+/// no condition holds.
+///
+/// ```
+/// # use cond::cond_rand;
+/// let healthy = [true, false, true];
+/// let backend = cond_rand! { &mut rand::rng();
+///     healthy[0] => "primary",
+///     healthy[1] => "secondary",
+///     healthy[2] => "tertiary",
+///     _ => "fallback",
+/// };
+/// assert!(backend == "primary" || backend == "tertiary");
+/// ```
+macro_rules! cond_rand {
+    ($rng:expr; $($condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?) => {{
+        let holds = [$($condition),*];
+        let chosen = $crate::__private::uniform_index($rng, &holds);
+        let mut arm = 0;
+        $(if { arm += 1; arm - 1 == chosen } { $value } else)*
+        { $($default)? }
+    }};
+}
+
+/// Returns the index of one of the `true` entries of `holds`, chosen uniformly, or `usize::MAX` if
+/// there are none.
+#[doc(hidden)]
+pub fn uniform_index<R: Rng + ?Sized>(rng: &mut R, holds: &[bool]) -> usize {
+    let count = holds.iter().filter(|&&holds| holds).count();
+    if count == 0 {
+        return usize::MAX;
+    }
+    let nth = rng.random_range(0..count);
+    holds
+        .iter()
+        .enumerate()
+        .filter(|(_, &holds)| holds)
+        .nth(nth)
+        .map_or(usize::MAX, |(index, _)| index)
+}
+
+/// Returns an index into `weights` chosen proportionally to the positive weights, or `usize::MAX`
+/// if there are none.
+#[doc(hidden)]
+pub fn weighted_index<R: Rng + ?Sized>(rng: &mut R, weights: &[f64]) -> usize {
+    let total: f64 = weights.iter().filter(|&&weight| weight > 0.0).sum();
+    if total <= 0.0 || !total.is_finite() {
+        return usize::MAX;
+    }
+    let mut target = rng.random::<f64>() * total;
+    let mut last = usize::MAX;
+    for (index, &weight) in weights.iter().enumerate() {
+        if weight > 0.0 {
+            if target < weight {
+                return index;
+            }
+            target -= weight;
+            last = index;
+        }
+    }
+    // Rounding can leave `target` just past the final weight.
+    last
+}
@@ -0,0 +1,224 @@
+//! A runtime table of key ranges, for threshold tables that come from configuration or are too
+//! long for a chain of comparisons.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Range, RangeFrom};
+
+#[macro_export]
+/// Builds a [`RangeCond`](crate::RangeCond) from `range => value` arms.
+///
+/// Each range is either bounded, `start..end`, or open-ended, `start..`. The arms may come in any
+/// order, and may leave gaps between them.
+///
+/// ```
+/// let grade = cond::range_cond! {
+///     90.. => 'A',
+///     80..90 => 'B',
+///     70..80 => 'C',
+///     0..60 => 'F',
+/// };
+/// assert_eq!(grade.get(&95), Some(&'A'));
+/// assert_eq!(grade.get(&85), Some(&'B'));
+/// assert_eq!(grade.get(&65), None);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a range is empty or two ranges overlap.
+macro_rules! range_cond {
+    ($($range:expr => $value:expr),+ $(,)?) => {
+        match $crate::RangeCond::from_bounds($crate::__private::vec![
+            $(($crate::__private::RangeBounds::into_bounds($range), $value)),+
+        ]) {
+            ::core::result::Result::Ok(table) => table,
+            ::core::result::Result::Err(error) => {
+                ::core::panic!("invalid `range_cond!` table: {}", error)
+            }
+        }
+    };
+}
+
+/// A table mapping disjoint ranges of keys to values, looked up by binary search.
+///
+/// This is the runtime counterpart of a [`cond!`](crate::cond) table whose conditions compare one
+/// key against thresholds. It is built from `(bound, value)` pairs with
+/// [`from_thresholds`](RangeCond::from_thresholds), or from `(range, value)` pairs with
+/// [`from_ranges`](RangeCond::from_ranges) and [`contiguous`](RangeCond::contiguous), which
+/// validate the table once so that every lookup takes `O(log n)` time.
+///
+/// ```
+/// use cond::RangeCond;
+///
+/// // Loaded from a configuration file, in no particular order.
+/// let tiers = vec![(10_000, "gold"), (0, "basic"), (1_000, "silver")];
+/// let tier = RangeCond::from_thresholds(tiers).unwrap();
+/// assert_eq!(tier.get(&50), Some(&"basic"));
+/// assert_eq!(tier.get(&1_000), Some(&"silver"));
+/// assert_eq!(tier.get(&1_000_000), Some(&"gold"));
+/// assert_eq!(tier.get(&-1), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeCond<K, V> {
+    /// Sorted by start, with each entry ending at or before the next one starts.
+    entries: Vec<(K, Option<K>, V)>,
+}
+
+/// The reason a [`RangeCond`] could not be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeError<K> {
+    /// A range contains no keys, because its end is not after its start.
+    Empty(Range<K>),
+    /// Two entries both cover the keys starting at the given bound, up to its end if any.
+    Overlap(K, Option<K>),
+    /// No entry covers the given range of keys, though [`contiguous`](RangeCond::contiguous)
+    /// requires every key between the first and last range to be covered.
+    Gap(Range<K>),
+}
+
+impl<K: Ord + Clone, V> RangeCond<K, V> {
+    /// Builds a table from `(bound, value)` pairs, where each value applies to keys from its bound
+    /// up to the next bound, and the value of the greatest bound applies to every key after it.
+    /// The pairs may come in any order, but no two bounds may be equal.
+    pub fn from_thresholds(
+        thresholds: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, RangeError<K>> {
+        let mut thresholds: Vec<(K, V)> = thresholds.into_iter().collect();
+        thresholds.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut entries: Vec<(K, Option<K>, V)> = Vec::with_capacity(thresholds.len());
+        for (bound, value) in thresholds {
+            if let Some((start, end, _)) = entries.last_mut() {
+                if *start == bound {
+                    return Err(RangeError::Overlap(bound, None));
+                }
+                *end = Some(bound.clone());
+            }
+            entries.push((bound, None, value));
+        }
+        Ok(RangeCond { entries })
+    }
+
+    /// Builds a table from `(range, value)` pairs, in any order. Keys outside every range have no
+    /// value.
+    pub fn from_ranges(
+        ranges: impl IntoIterator<Item = (Range<K>, V)>,
+    ) -> Result<Self, RangeError<K>> {
+        Self::from_bounds(
+            ranges
+                .into_iter()
+                .map(|(range, value)| ((range.start, Some(range.end)), value)),
+        )
+    }
+
+    /// Like [`from_ranges`](RangeCond::from_ranges), but also rejects gaps between the ranges, so
+    /// that every key from the lowest start to the highest end has a value.
+    pub fn contiguous(
+        ranges: impl IntoIterator<Item = (Range<K>, V)>,
+    ) -> Result<Self, RangeError<K>> {
+        let table = Self::from_ranges(ranges)?;
+        for pair in table.entries.windows(2) {
+            if let [(_, Some(end), _), (start, _, _)] = pair {
+                if end < start {
+                    return Err(RangeError::Gap(end.clone()..start.clone()));
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    #[doc(hidden)]
+    pub fn from_bounds(
+        bounds: impl IntoIterator<Item = ((K, Option<K>), V)>,
+    ) -> Result<Self, RangeError<K>> {
+        let mut entries: Vec<(K, Option<K>, V)> = Vec::new();
+        for ((start, end), value) in bounds {
+            if let Some(end) = end.as_ref().filter(|end| **end <= start) {
+                return Err(RangeError::Empty(start..end.clone()));
+            }
+            entries.push((start, end, value));
+        }
+        entries.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+        for pair in entries.windows(2) {
+            let [(_, end, _), (start, next_end, _)] = pair else {
+                unreachable!()
+            };
+            if end.as_ref().is_none_or(|end| end > start) {
+                let overlap_end = match (end, next_end) {
+                    (Some(end), Some(next_end)) => Some(end.min(next_end).clone()),
+                    (end, next_end) => end.clone().or_else(|| next_end.clone()),
+                };
+                return Err(RangeError::Overlap(start.clone(), overlap_end));
+            }
+        }
+        Ok(RangeCond { entries })
+    }
+}
+
+impl<K: Ord, V> RangeCond<K, V> {
+    /// Returns the value of the range containing `key`, or `None` if no range does.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.position(key).map(|index| &self.entries[index].2)
+    }
+
+    /// Returns the index, in order of their starts, of the range containing `key`.
+    pub fn position(&self, key: &K) -> Option<usize> {
+        let after = self.entries.partition_point(|(start, _, _)| start <= key);
+        let index = after.checked_sub(1)?;
+        let (_, end, _) = &self.entries[index];
+        end.as_ref().is_none_or(|end| key < end).then_some(index)
+    }
+
+    /// Returns the number of ranges.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether there are no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the ranges in order, as their start, their end if they have one, and their
+    /// value.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Option<&K>, &V)> {
+        self.entries
+            .iter()
+            .map(|(start, end, value)| (start, end.as_ref(), value))
+    }
+}
+
+impl<K: fmt::Debug> fmt::Display for RangeError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Empty(range) => write!(f, "the range {:?} is empty", range),
+            RangeError::Overlap(start, Some(end)) => {
+                write!(f, "more than one range covers {:?}", start..end)
+            }
+            RangeError::Overlap(start, None) => {
+                write!(f, "more than one range covers {:?}", start..)
+            }
+            RangeError::Gap(range) => write!(f, "no range covers {:?}", range),
+        }
+    }
+}
+
+impl<K: fmt::Debug> core::error::Error for RangeError<K> {}
+
+/// Converts the ranges accepted by [`range_cond!`](crate::range_cond) into a start and an optional
+/// end.
+#[doc(hidden)]
+pub trait RangeBounds<K> {
+    fn into_bounds(self) -> (K, Option<K>);
+}
+
+impl<K> RangeBounds<K> for Range<K> {
+    fn into_bounds(self) -> (K, Option<K>) {
+        (self.start, Some(self.end))
+    }
+}
+
+impl<K> RangeBounds<K> for RangeFrom<K> {
+    fn into_bounds(self) -> (K, Option<K>) {
+        (self.start, None)
+    }
+}
@@ -0,0 +1,39 @@
+//! Condition-driven state transitions.
+
+#[macro_export]
+/// Consumes a state value and returns the result of the first transition whose condition holds,
+/// or the state unchanged if none does.
+///
+/// The first argument names the state, followed by a semicolon: either a variable, which is moved
+/// into the macro, or `name = expression`. Conditions can inspect the state by name, and each
+/// transition can consume it. A `_` arm replaces the default of returning the state unchanged.
+///
+/// ```
+/// # use cond::cond_reduce;
+/// #[derive(Debug, PartialEq)]
+/// enum Door { Open, Closed, Locked }
+///
+/// fn update(door: Door, push: bool, has_key: bool) -> Door {
+///     cond_reduce! { door;
+///         door == Door::Locked && has_key => Door::Closed,
+///         door == Door::Closed && push => Door::Open,
+///         door == Door::Open && !push => Door::Closed,
+///     }
+/// }
+///
+/// assert_eq!(update(Door::Locked, true, false), Door::Locked);
+/// assert_eq!(update(Door::Closed, true, false), Door::Open);
+/// ```
+macro_rules! cond_reduce {
+    ($state:ident = $initial:expr; $($condition:expr => $transition:expr),* , _ => $default:expr $(,)?) => {{
+        let $state = $initial;
+        $(if $condition { $transition } else)* { $default }
+    }};
+    ($state:ident = $initial:expr; $($condition:expr => $transition:expr),* $(,)?) => {{
+        let $state = $initial;
+        $(if $condition { $transition } else)* { $state }
+    }};
+    ($state:ident; $($arms:tt)*) => {
+        $crate::cond_reduce! { $state = $state; $($arms)* }
+    };
+}
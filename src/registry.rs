@@ -0,0 +1,52 @@
+//! A registry of the tables declared with [`cond_named!`](crate::cond_named), for tooling that
+//! lists decision points and their arms at runtime.
+//!
+//! Every `cond_named!` invocation in the program registers a [`Site`] when the program starts,
+//! whether or not it is ever evaluated.
+//!
+//! ```
+//! # use cond::cond_named;
+//! fn lookup(cached: bool) -> &'static str {
+//!     cond_named! { "docs.lookup";
+//!         "cache_hit": cached => "cache",
+//!         _ => "database",
+//!     }
+//! }
+//!
+//! let site = cond::registry::site("docs.lookup").unwrap();
+//! assert_eq!(site.arms, ["cache_hit"]);
+//! assert_eq!(site.position("cache_hit"), Some(0));
+//! # assert_eq!(lookup(true), "cache");
+//! ```
+
+/// A registered table.
+#[derive(Debug)]
+pub struct Site {
+    /// The name given to the table.
+    pub name: &'static str,
+    /// The names of the arms, in order, not counting the default arm.
+    pub arms: &'static [&'static str],
+    /// The file containing the table.
+    pub file: &'static str,
+    /// The line of the table in its file.
+    pub line: u32,
+}
+
+impl Site {
+    /// Returns the index of the arm named `arm`.
+    pub fn position(&self, arm: &str) -> Option<usize> {
+        self.arms.iter().position(|&name| name == arm)
+    }
+}
+
+inventory::collect!(Site);
+
+/// Returns every registered table, in no particular order.
+pub fn sites() -> impl Iterator<Item = &'static Site> {
+    inventory::iter::<Site>.into_iter()
+}
+
+/// Returns a registered table named `name`.
+pub fn site(name: &str) -> Option<&'static Site> {
+    sites().find(|site| site.name == name)
+}
@@ -0,0 +1,43 @@
+//! A loop that evaluates a [`cond!`](crate::cond) table on every iteration.
+
+#[macro_export]
+/// Evaluates a [`cond!`](crate::cond) table over and over, running the first arm whose condition
+/// holds on each iteration, until an arm breaks out of the loop or no condition holds.
+///
+/// An arm ends the loop by evaluating to `break`, or to `break value` to make the loop evaluate to
+/// `value`. Without a default arm, the loop ends with `()` when no condition holds, so tables that
+/// break with a value need a default arm, which runs when no condition holds as in
+/// [`cond!`](crate::cond) and usually breaks too. The arms can be anything `cond!` accepts,
+/// including `@unless` arms and nested tables.
+///
+/// ```
+/// # use cond::while_cond;
+/// let mut queue = vec![3, 0, 8, 1];
+/// let mut done = Vec::new();
+/// while_cond! {
+///     queue.last() == Some(&0) => {
+///         queue.pop();
+///     },
+///     !queue.is_empty() => done.push(queue.pop().unwrap() * 10),
+/// }
+/// assert_eq!(done, [10, 80, 30]);
+///
+/// let (mut position, mut velocity) = (0_i32, 8_i32);
+/// let landed = while_cond! {
+///     position < 0 => break Err(position),
+///     velocity == 0 => break Ok(position),
+///     _ => {
+///         position += velocity;
+///         velocity -= 1;
+///     },
+/// };
+/// assert_eq!(landed, Ok(36));
+/// ```
+macro_rules! while_cond {
+    ($($arms:tt)*) => {
+        loop {
+            // Without a default arm, the loop breaks when no condition holds.
+            $crate::cond! { @parse { break } [] $($arms)* }
+        }
+    };
+}
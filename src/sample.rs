@@ -0,0 +1,248 @@
+//! Sampling of the evaluations that instrumentation records.
+//!
+//! Recording every evaluation of a hot table can cost more than the table itself. A [`Sampler`]
+//! decides which evaluations are recorded, with a [`Rate`] for each table and a default rate for
+//! the others, all of which can be changed at runtime. The [`Sampled`](crate::audit::Sampled)
+//! audit sink samples the records passed to another sink, and with the `otel` feature,
+//! [`otel::SAMPLER`](crate::otel::SAMPLER) samples the spans of named tables. These need the
+//! `std` feature; without it, a [`SiteSampler`] samples the evaluations of a single table.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::{sync::Mutex, vec::Vec};
+
+/// How many of a table's evaluations to record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rate {
+    one_in: u32,
+    non_default_only: bool,
+}
+
+impl Rate {
+    /// Records every evaluation.
+    pub const ALL: Rate = Rate::one_in(1);
+
+    /// Records no evaluations.
+    pub const NONE: Rate = Rate::one_in(0);
+
+    /// Records the first of every `n` evaluations, or none if `n` is 0.
+    pub const fn one_in(n: u32) -> Self {
+        Rate {
+            one_in: n,
+            non_default_only: false,
+        }
+    }
+
+    /// Only records evaluations that selected an arm other than the default one, and only counts
+    /// those towards the `n` of [`one_in`](Rate::one_in).
+    pub const fn non_default_only(self) -> Self {
+        Rate {
+            non_default_only: true,
+            ..self
+        }
+    }
+}
+
+impl Default for Rate {
+    fn default() -> Self {
+        Rate::ALL
+    }
+}
+
+/// The sampling rate of a single table, which can be changed while the program runs, and which
+/// needs neither `std` nor an allocator.
+///
+/// ```
+/// use cond::audit::Record;
+/// use cond::cond_audit;
+/// use cond::sample::{Rate, SiteSampler};
+/// use core::cell::Cell;
+///
+/// static SAMPLE: SiteSampler = SiteSampler::new(Rate::one_in(4));
+///
+/// let recorded = Cell::new(0);
+/// let sink = |record: &Record| {
+///     if SAMPLE.sample(record.arm) {
+///         recorded.set(recorded.get() + 1);
+///     }
+/// };
+/// for reading in 0..10 {
+///     cond_audit! { sink, "sensor.range"; reading > 100 => "overrange", _ => "ok" };
+/// }
+/// assert_eq!(recorded.get(), 3);
+/// ```
+#[derive(Debug)]
+pub struct SiteSampler {
+    one_in: AtomicU32,
+    non_default_only: AtomicBool,
+    seen: AtomicUsize,
+}
+
+impl SiteSampler {
+    /// Creates a sampler with the given rate.
+    pub const fn new(rate: Rate) -> Self {
+        SiteSampler {
+            one_in: AtomicU32::new(rate.one_in),
+            non_default_only: AtomicBool::new(rate.non_default_only),
+            seen: AtomicUsize::new(0),
+        }
+    }
+
+    /// Changes the rate, and restarts the count of evaluations. An evaluation sampled at the same
+    /// time may see the old rate, the new one, or a mix of the two.
+    pub fn set(&self, rate: Rate) {
+        self.one_in.store(rate.one_in, Ordering::Relaxed);
+        self.non_default_only
+            .store(rate.non_default_only, Ordering::Relaxed);
+        self.seen.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the rate.
+    pub fn rate(&self) -> Rate {
+        Rate {
+            one_in: self.one_in.load(Ordering::Relaxed),
+            non_default_only: self.non_default_only.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Counts an evaluation that selected `arm`, in the form of
+    /// [`Record::arm`](crate::audit::Record::arm), and returns whether to record it.
+    pub fn sample(&self, arm: Option<usize>) -> bool {
+        let rate = self.rate();
+        if rate.non_default_only && arm.is_none() {
+            return false;
+        }
+        let first = self.seen.fetch_add(1, Ordering::Relaxed);
+        // The count wraps around, which only matters for rates that do not divide its range.
+        rate.one_in != 0 && first as u64 % u64::from(rate.one_in) == 0
+    }
+}
+
+impl Default for SiteSampler {
+    fn default() -> Self {
+        SiteSampler::new(Rate::ALL)
+    }
+}
+
+/// Per-table sampling rates, which can be changed while the program runs.
+///
+/// Each table with its own rate counts its evaluations separately, and the tables using the
+/// default rate share one count. Until a rate is set, every evaluation is recorded without
+/// locking anything.
+///
+/// ```
+/// use cond::sample::{Rate, Sampler};
+///
+/// let sampler = Sampler::new();
+/// sampler.set_default(Rate::one_in(100));
+/// sampler.set("checkout.fraud", Rate::ALL.non_default_only());
+///
+/// // The first of every 100 evaluations of other tables is recorded.
+/// let recorded = (0..1000).filter(|_| sampler.sample("feed.rank", Some(0))).count();
+/// assert_eq!(recorded, 10);
+///
+/// // Every evaluation of the fraud check is recorded, unless it selected the default arm.
+/// assert!(sampler.sample("checkout.fraud", Some(1)));
+/// assert!(!sampler.sample("checkout.fraud", None));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct Sampler {
+    configured: AtomicBool,
+    state: Mutex<State>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct State {
+    default: Counter,
+    sites: Vec<(&'static str, Counter)>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct Counter {
+    rate: Rate,
+    seen: u64,
+}
+
+#[cfg(feature = "std")]
+impl Counter {
+    fn sample(&mut self, arm: Option<usize>) -> bool {
+        if self.rate.non_default_only && arm.is_none() {
+            return false;
+        }
+        let first = self.seen;
+        self.seen += 1;
+        self.rate.one_in != 0 && first % u64::from(self.rate.one_in) == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sampler {
+    /// Creates a sampler that records every evaluation.
+    pub const fn new() -> Self {
+        Sampler {
+            configured: AtomicBool::new(false),
+            state: Mutex::new(State {
+                default: Counter {
+                    rate: Rate::ALL,
+                    seen: 0,
+                },
+                sites: Vec::new(),
+            }),
+        }
+    }
+
+    /// Sets the rate of the tables without a rate of their own.
+    pub fn set_default(&self, rate: Rate) {
+        self.lock().default = Counter { rate, seen: 0 };
+        self.configured.store(true, Ordering::Release);
+    }
+
+    /// Sets the rate of the table named `site`.
+    pub fn set(&self, site: &'static str, rate: Rate) {
+        let mut state = self.lock();
+        let counter = Counter { rate, seen: 0 };
+        match state.sites.iter_mut().find(|(name, _)| *name == site) {
+            Some((_, existing)) => *existing = counter,
+            None => state.sites.push((site, counter)),
+        }
+        self.configured.store(true, Ordering::Release);
+    }
+
+    /// Makes the table named `site` use the default rate again, returning whether it had a rate of
+    /// its own.
+    pub fn unset(&self, site: &'static str) -> bool {
+        let mut state = self.lock();
+        let before = state.sites.len();
+        state.sites.retain(|(name, _)| *name != site);
+        before != state.sites.len()
+    }
+
+    /// Returns the rate of the table named `site`.
+    pub fn rate(&self, site: &'static str) -> Rate {
+        let state = self.lock();
+        let counter = state.sites.iter().find(|(name, _)| *name == site);
+        counter.map_or(state.default.rate, |(_, counter)| counter.rate)
+    }
+
+    /// Counts an evaluation of the table named `site` that selected `arm`, in the form of
+    /// [`Record::arm`](crate::audit::Record::arm), and returns whether to record it.
+    pub fn sample(&self, site: &'static str, arm: Option<usize>) -> bool {
+        if !self.configured.load(Ordering::Acquire) {
+            return true;
+        }
+        let mut state = self.lock();
+        let State { default, sites } = &mut *state;
+        let counter = sites.iter_mut().find(|(name, _)| *name == site);
+        counter.map_or(default, |(_, counter)| counter).sample(arm)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Counters are only ever updated in place, so a panic cannot leave them inconsistent.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
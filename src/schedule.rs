@@ -0,0 +1,151 @@
+//! Conditions that hold during recurring time windows, such as business hours or a weekly
+//! maintenance window.
+//!
+//! A [`Schedule`] reads the wall-clock time from a [`Clock`] whose origin is the Unix epoch, such
+//! as [`SystemClock`](crate::clock::SystemClock), or a [`ManualClock`](crate::clock::ManualClock)
+//! set to a known time in tests. Guard an arm with [`is_active`](Schedule::is_active) to make it
+//! eligible only inside the window:
+//!
+//! ```
+//! # use cond::cond;
+//! use cond::clock::ManualClock;
+//! use cond::schedule::{Days, Schedule};
+//! use std::time::Duration;
+//!
+//! // Thursday 1 January 1970, 10:00 UTC.
+//! let clock = ManualClock::new();
+//! clock.set(Duration::from_secs(10 * 3600));
+//!
+//! let business_hours = Schedule::new(&clock).days(Days::WEEKDAYS).between((9, 0), (17, 30));
+//! let maintenance = Schedule::new(&clock).days(Days::SUNDAY).between((2, 0), (4, 0));
+//! let route = || cond! {
+//!     maintenance.is_active() => "maintenance page",
+//!     business_hours.is_active() => "live agent",
+//!     _ => "voicemail",
+//! };
+//! assert_eq!(route(), "live agent");
+//! clock.advance(Duration::from_secs(8 * 3600));
+//! assert_eq!(route(), "voicemail");
+//! ```
+
+use core::ops::BitOr;
+
+use crate::clock::Clock;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A set of days of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Days(u8);
+
+impl Days {
+    /// Monday only.
+    pub const MONDAY: Days = Days(1 << 0);
+    /// Tuesday only.
+    pub const TUESDAY: Days = Days(1 << 1);
+    /// Wednesday only.
+    pub const WEDNESDAY: Days = Days(1 << 2);
+    /// Thursday only.
+    pub const THURSDAY: Days = Days(1 << 3);
+    /// Friday only.
+    pub const FRIDAY: Days = Days(1 << 4);
+    /// Saturday only.
+    pub const SATURDAY: Days = Days(1 << 5);
+    /// Sunday only.
+    pub const SUNDAY: Days = Days(1 << 6);
+    /// Monday to Friday.
+    pub const WEEKDAYS: Days = Days(0b001_1111);
+    /// Saturday and Sunday.
+    pub const WEEKEND: Days = Days(0b110_0000);
+    /// Every day of the week.
+    pub const EVERY_DAY: Days = Days(0b111_1111);
+
+    /// Returns whether the set contains the day `weekday` days after Monday.
+    fn contains(self, weekday: i64) -> bool {
+        self.0 & (1 << weekday) != 0
+    }
+}
+
+impl BitOr for Days {
+    type Output = Days;
+
+    fn bitor(self, other: Days) -> Days {
+        Days(self.0 | other.0)
+    }
+}
+
+/// A condition that holds during a daily time window, on some days of the week.
+///
+/// A window whose end is not after its start runs past midnight into the next day, and belongs to
+/// the day it starts on. By default, a schedule is active all day, every day, in UTC.
+#[derive(Debug, Clone)]
+pub struct Schedule<C> {
+    clock: C,
+    days: Days,
+    start: i64,
+    end: i64,
+    offset: i64,
+}
+
+impl<C: Clock> Schedule<C> {
+    /// Creates a schedule that is always active, reading the time since the Unix epoch from
+    /// `clock`.
+    pub fn new(clock: C) -> Self {
+        Schedule {
+            clock,
+            days: Days::EVERY_DAY,
+            start: 0,
+            end: 0,
+            offset: 0,
+        }
+    }
+
+    /// Restricts the schedule to windows starting on `days`.
+    pub fn days(mut self, days: Days) -> Self {
+        self.days = days;
+        self
+    }
+
+    /// Restricts the schedule to the time of day from `start` up to `end`, each an hour and a
+    /// minute.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an hour is above 23 or a minute is above 59.
+    pub fn between(mut self, start: (u8, u8), end: (u8, u8)) -> Self {
+        self.start = time_of_day(start);
+        self.end = time_of_day(end);
+        self
+    }
+
+    /// Interprets the days and times in the time zone `minutes` ahead of UTC, or behind it if
+    /// negative.
+    pub fn utc_offset_minutes(mut self, minutes: i32) -> Self {
+        self.offset = i64::from(minutes) * 60;
+        self
+    }
+
+    /// Returns whether the clock's current time is inside the schedule.
+    pub fn is_active(&self) -> bool {
+        let now = self.clock.now().as_secs() as i64 + self.offset;
+        let day = now.div_euclid(SECS_PER_DAY);
+        let time = now.rem_euclid(SECS_PER_DAY);
+        // The Unix epoch was a Thursday.
+        let weekday = (day + 3).rem_euclid(7);
+        if self.start < self.end {
+            self.days.contains(weekday) && (self.start..self.end).contains(&time)
+        } else {
+            let yesterday = (weekday + 6) % 7;
+            (self.days.contains(weekday) && time >= self.start)
+                || (self.days.contains(yesterday) && time < self.end)
+        }
+    }
+}
+
+fn time_of_day((hour, minute): (u8, u8)) -> i64 {
+    assert!(
+        hour < 24 && minute < 60,
+        "invalid time of day {hour}:{minute:02}"
+    );
+    (i64::from(hour) * 60 + i64::from(minute)) * 60
+}
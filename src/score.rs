@@ -0,0 +1,123 @@
+//! Selection of the best-scoring arm instead of the first true one.
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+#[macro_export]
+/// Evaluates a score for every arm and evaluates to the value of the arm with the highest score.
+///
+/// Each arm is `score => value`, where all scores have the same [`PartialOrd`] type, such as `f32`
+/// or `i32`. Ties go to the earliest arm, and scores that are not comparable to themselves, like
+/// `NaN`, never win unless every score is. Every score is evaluated, then only the chosen arm's
+/// value.
+///
+/// ```
+/// # use cond::score_cond;
+/// let (health, enemies, ammo) = (0.3_f32, 2.0_f32, 0.0_f32);
+/// let action = score_cond! {
+///     1.0 - health => "heal",
+///     enemies * 0.3 * ammo.min(1.0) => "attack",
+///     enemies * 0.2 => "flee",
+///     0.1 => "wander",
+/// };
+/// assert_eq!(action, "heal");
+/// ```
+///
+/// For score tables built at runtime, see `Scorer`, which needs the `alloc` feature.
+macro_rules! score_cond {
+    ($($score:expr => $value:expr),+ $(,)?) => {{
+        let chosen = $crate::__private::best_index(&[$($score),+]);
+        let mut arm = 0;
+        $(if { arm += 1; arm - 1 == chosen } { $value } else)+ {
+            ::core::unreachable!()
+        }
+    }};
+}
+
+/// Returns the index of the first greatest score, skipping scores that are not comparable to
+/// themselves, or 0 if there are no comparable scores.
+#[doc(hidden)]
+pub fn best_index<S: PartialOrd>(scores: &[S]) -> usize {
+    let mut best: Option<usize> = None;
+    for (index, score) in scores.iter().enumerate() {
+        if score.partial_cmp(score).is_none() {
+            continue;
+        }
+        if best.is_none_or(|best| *score > scores[best]) {
+            best = Some(index);
+        }
+    }
+    best.unwrap_or(0)
+}
+
+/// A runtime table of scored options, where the option with the highest score for an input wins.
+///
+/// This is the runtime counterpart of [`score_cond!`](crate::score_cond), with the same rules for
+/// ties and incomparable scores.
+///
+/// ```
+/// use cond::Scorer;
+///
+/// struct Agent { health: f32, enemies: f32 }
+///
+/// let scorer = Scorer::new()
+///     .option(|a: &Agent| 1.0 - a.health, "heal")
+///     .option(|a: &Agent| a.enemies * 0.25, "attack");
+///
+/// assert_eq!(scorer.best(&Agent { health: 0.9, enemies: 3.0 }), Some(&"attack"));
+/// assert_eq!(scorer.best(&Agent { health: 0.2, enemies: 3.0 }), Some(&"heal"));
+/// ```
+#[cfg(feature = "alloc")]
+pub struct Scorer<'a, I, T, S = f32> {
+    options: Vec<(ScoreFn<'a, I, S>, T)>,
+}
+
+#[cfg(feature = "alloc")]
+type ScoreFn<'a, I, S> = Box<dyn Fn(&I) -> S + 'a>;
+
+#[cfg(feature = "alloc")]
+impl<'a, I, T, S: PartialOrd> Scorer<'a, I, T, S> {
+    /// Creates a scorer with no options.
+    pub fn new() -> Self {
+        Scorer {
+            options: Vec::new(),
+        }
+    }
+
+    /// Adds an option, selected when `score` rates it highest.
+    pub fn option(mut self, score: impl Fn(&I) -> S + 'a, value: T) -> Self {
+        self.options.push((Box::new(score), value));
+        self
+    }
+
+    /// Returns the number of options.
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    /// Returns whether there are no options.
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    /// Returns the index of the best option for `input`, or `None` if there are no options.
+    pub fn best_index(&self, input: &I) -> Option<usize> {
+        if self.options.is_empty() {
+            return None;
+        }
+        let scores: Vec<S> = self.options.iter().map(|(score, _)| score(input)).collect();
+        Some(best_index(&scores))
+    }
+
+    /// Returns the value of the best option for `input`, or `None` if there are no options.
+    pub fn best(&self, input: &I) -> Option<&T> {
+        self.best_index(input).map(|index| &self.options[index].1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T, S: PartialOrd> Default for Scorer<'_, I, T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
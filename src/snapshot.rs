@@ -0,0 +1,62 @@
+//! A variant of [`cond!`](crate::cond) that evaluates every condition before selecting an arm, and
+//! its alias [`cond_strict!`].
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but evaluates every condition first, in order, and only then
+/// selects the first arm whose condition held.
+///
+/// The conditions therefore see a consistent state: one arm's value can no longer run before a
+/// later condition is checked, and conditions reading shared atomics or locks are read together
+/// rather than interleaved with the values. The cost is that every condition is always evaluated.
+///
+/// Because nothing short-circuits, this is also the variant to use when every condition must run
+/// for its own effects, such as probes that record metrics or health checks that must all be
+/// polled, while the value of the first arm whose condition held is still the one selected.
+///
+/// ```
+/// # use cond::cond_snapshot;
+/// use std::sync::atomic::{AtomicU32, Ordering};
+///
+/// let queued = AtomicU32::new(3);
+/// let take = || queued.fetch_sub(1, Ordering::Relaxed);
+///
+/// let (level, left) = cond_snapshot! {
+///     take() > 2 => ("high", queued.load(Ordering::Relaxed)),
+///     take() > 1 => ("medium", queued.load(Ordering::Relaxed)),
+///     _ => ("low", queued.load(Ordering::Relaxed)),
+/// };
+/// // Both conditions ran before the first arm's value.
+/// assert_eq!((level, left), ("high", 1));
+/// ```
+macro_rules! cond_snapshot {
+    ($($condition:expr => $value:expr),* $(, _ => $default:expr)? $(,)?) => {{
+        let held: &[bool] = &[$($condition),*];
+        let mut arm = 0;
+        $(if { arm += 1; held[arm - 1] } { $value } else)*
+        { $($default)? }
+    }};
+}
+
+#[macro_export]
+/// Another name for [`cond_snapshot!`], for tables whose conditions must all run for their
+/// effects, while the first arm whose condition held is still the one selected.
+///
+/// ```
+/// # use cond::cond_strict;
+/// let mut polled = 0;
+/// let mut poll = |healthy: bool| {
+///     polled += 1;
+///     healthy
+/// };
+/// let status = cond_strict! {
+///     poll(true) => "up",
+///     poll(false) => "degraded",
+///     _ => "down",
+/// };
+/// assert_eq!((status, polled), ("up", 2));
+/// ```
+macro_rules! cond_strict {
+    ($($arms:tt)*) => {
+        $crate::cond_snapshot! { $($arms)* }
+    };
+}
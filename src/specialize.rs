@@ -0,0 +1,77 @@
+//! Dispatch decided once, on first use.
+
+#[macro_export]
+/// Defines a function that evaluates a [`cond!`](crate::cond) table the first time it is called,
+/// and returns the same selected value on every later call.
+///
+/// This suits probes of things that do not change while the program runs, such as the kernel
+/// version or the capabilities of the terminal. The function must take no arguments and return
+/// `&'static T`, where the table's values have type `T`, and the table must have a default arm.
+/// The value is stored in a [`OnceLock`](std::sync::OnceLock), so concurrent first calls evaluate
+/// the table only once.
+///
+/// ```
+/// # use cond::specialize;
+/// # fn quiet_requested() -> bool { false }
+/// # fn colors_supported() -> bool { true }
+/// #[derive(Debug, PartialEq)]
+/// enum Output { Color, Plain, Silent }
+///
+/// specialize! {
+///     /// How to print progress.
+///     pub fn output() -> &'static Output {
+///         quiet_requested() => Output::Silent,
+///         colors_supported() => Output::Color,
+///         _ => Output::Plain,
+///     }
+/// }
+///
+/// assert_eq!(output(), &Output::Color);
+/// ```
+macro_rules! specialize {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident() -> &'static $type:ty { $($arms:tt)* }
+    ) => {
+        $(#[$attr])*
+        $vis fn $name() -> &'static $type {
+            static CHOSEN: $crate::__private::OnceLock<$type> = $crate::__private::OnceLock::new();
+            CHOSEN.get_or_init(|| $crate::cond! { $($arms)* })
+        }
+    };
+}
+
+#[macro_export]
+/// Evaluates a [`cond!`](crate::cond) table the first time the invocation runs, and evaluates to
+/// a `&'static` reference to the same selected value every later time it runs.
+///
+/// The type of the values comes first, followed by a semicolon and the arms, which must include a
+/// default arm. Like [`specialize!`](crate::specialize), the value is stored in a
+/// [`OnceLock`](std::sync::OnceLock) belonging to the invocation, so the table is evaluated once
+/// per call site, not once per enclosing function call or per thread. This suits startup
+/// detection that would be wasteful to repeat, such as whether the program runs in a container or
+/// whether its output is a terminal.
+///
+/// ```
+/// # use cond::cond_once;
+/// # use std::io::IsTerminal;
+/// fn progress_style() -> &'static str {
+///     cond_once! { &'static str;
+///         std::env::var_os("NO_COLOR").is_some() => "plain",
+///         std::io::stderr().is_terminal() => "color",
+///         _ => "plain",
+///     }
+/// }
+///
+/// assert_eq!(progress_style(), progress_style());
+/// ```
+///
+/// The table runs in a closure inside a `static`, so its arms cannot use `return`, `break` or `?`
+/// on the surrounding function, and the type cannot use the generic parameters of the surrounding
+/// function.
+macro_rules! cond_once {
+    ($type:ty; $($arms:tt)*) => {{
+        static CHOSEN: $crate::__private::OnceLock<$type> = $crate::__private::OnceLock::new();
+        CHOSEN.get_or_init(|| $crate::cond! { $($arms)* })
+    }};
+}
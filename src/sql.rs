@@ -0,0 +1,36 @@
+//! A function and the equivalent SQL `CASE` expression from one table.
+
+/// Defines a function whose body is a [`cond!`](crate::cond) table, along with a constant holding
+/// the same table as a SQL `CASE WHEN ... THEN ... ELSE ... END` expression, so that the logic can
+/// run both in Rust and in a query without the two drifting apart.
+///
+/// The table is restricted to what translates directly: conditions may only use the function's
+/// parameters, literals, the comparison operators, `!`, `&&`, `||` and parentheses, and values may
+/// only be parameters or literals. A default arm is required, and becomes the `ELSE`. Each
+/// parameter is read from the column of the same name, unless it has a `#[column = "name"]`
+/// attribute. The constant is named after the function, in upper case, followed by `_SQL`.
+///
+/// ```
+/// # use cond::cond_sql;
+/// cond_sql! {
+///     /// The shipping tier of an order.
+///     fn shipping(#[column = "o.total"] total: f64, country: &str, express: bool) -> &'static str {
+///         express && country == "NL" => "same day",
+///         express => "next day",
+///         total >= 50.0 || country == "NL" => "free",
+///         _ => "standard",
+///     }
+/// }
+///
+/// assert_eq!(shipping(20.0, "DE", true), "next day");
+/// assert_eq!(shipping(80.0, "DE", false), "free");
+/// assert_eq!(
+///     SHIPPING_SQL,
+///     "CASE WHEN express AND country = 'NL' THEN 'same day' WHEN express THEN 'next day' \
+///      WHEN o.total >= 50.0 OR country = 'NL' THEN 'free' ELSE 'standard' END"
+/// );
+/// ```
+///
+/// The SQL embeds the table's literals and column names as written, so it is only as trustworthy
+/// as the source of the macro invocation; it never contains runtime values.
+pub use cond_macros::cond_sql;
@@ -0,0 +1,167 @@
+//! Tables of function pointers that can live in `static`s.
+
+use core::fmt;
+
+#[macro_export]
+/// Declares a `static` [`StaticCond`](crate::StaticCond), a table of predicate and handler
+/// function pointers walked at runtime.
+///
+/// Each arm is `predicate => handler`, where the predicate is a `fn(&I) -> bool` and the handler
+/// a `fn(&I) -> T`, given either as paths to functions or as closures that capture nothing. A
+/// default arm, `_ => handler`, may come last. The source of each predicate is kept as the arm's
+/// name.
+///
+/// ```
+/// use cond::{cond_static, StaticCond};
+///
+/// struct Request { method: &'static str, path: &'static str }
+///
+/// fn is_health_check(request: &Request) -> bool {
+///     request.path == "/health"
+/// }
+///
+/// cond_static! {
+///     /// The routes of the server.
+///     pub static ROUTES: StaticCond<Request, u16> = {
+///         is_health_check => |_| 204,
+///         |r| r.method == "GET" => |_| 200,
+///         _ => |_| 405,
+///     };
+/// }
+///
+/// assert_eq!(ROUTES.eval(&Request { method: "GET", path: "/health" }), Some(204));
+/// assert_eq!(ROUTES.eval(&Request { method: "PUT", path: "/users" }), Some(405));
+/// assert_eq!(ROUTES.arms()[1].name, "|r| r.method == \"GET\"");
+/// ```
+macro_rules! cond_static {
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident: $ty:ty = {
+            $($predicate:expr => $handler:expr),* $(, _ => $default:expr)? $(,)?
+        };
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $ty = $crate::cond_static!(@new [$($crate::StaticArm {
+            name: ::core::stringify!($predicate),
+            predicate: $predicate,
+            handler: $handler,
+        }),*] $($default)?);
+    };
+    (@new [$($arm:expr),*]) => {
+        $crate::StaticCond::new(&[$($arm),*])
+    };
+    (@new [$($arm:expr),*] $default:expr) => {
+        $crate::StaticCond::with_default(&[$($arm),*], $default)
+    };
+}
+
+/// A table of arms whose conditions and values are function pointers, so that it can be built in
+/// a `static` with [`cond_static!`](crate::cond_static), shared between threads, and inspected
+/// at runtime.
+pub struct StaticCond<I: ?Sized + 'static, T: 'static> {
+    arms: &'static [StaticArm<I, T>],
+    default: Option<fn(&I) -> T>,
+}
+
+/// An arm of a [`StaticCond`].
+pub struct StaticArm<I: ?Sized + 'static, T: 'static> {
+    /// The source of the predicate.
+    pub name: &'static str,
+    /// The condition of the arm.
+    pub predicate: fn(&I) -> bool,
+    /// Computes the value of the arm.
+    pub handler: fn(&I) -> T,
+}
+
+impl<I: ?Sized, T> StaticCond<I, T> {
+    /// Creates a table from its arms, with no default arm.
+    pub const fn new(arms: &'static [StaticArm<I, T>]) -> Self {
+        StaticCond {
+            arms,
+            default: None,
+        }
+    }
+
+    /// Creates a table from its arms and the handler of its default arm.
+    pub const fn with_default(arms: &'static [StaticArm<I, T>], default: fn(&I) -> T) -> Self {
+        StaticCond {
+            arms,
+            default: Some(default),
+        }
+    }
+
+    /// Returns the value of the first arm whose predicate holds for `input`, or of the default
+    /// arm, or `None` if no predicate holds and there is no default arm.
+    pub fn eval(&self, input: &I) -> Option<T> {
+        match self.position(input) {
+            Some(index) => Some((self.arms[index].handler)(input)),
+            None => self.default.map(|default| default(input)),
+        }
+    }
+
+    /// Returns the index of the first arm whose predicate holds for `input`.
+    pub fn position(&self, input: &I) -> Option<usize> {
+        self.arms.iter().position(|arm| (arm.predicate)(input))
+    }
+
+    /// Returns the arms, in order.
+    pub fn arms(&self) -> &'static [StaticArm<I, T>] {
+        self.arms
+    }
+
+    /// Returns whether the table has a default arm.
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: core::hash::Hash + Eq + Clone, T> StaticCond<I, T> {
+    /// Like [`eval`](StaticCond::eval), but looks up the arm for `input` in `cache` before
+    /// evaluating the predicates.
+    ///
+    /// A cache holds the arms of one table. Using it with another table first forgets every cached
+    /// arm.
+    ///
+    /// ```
+    /// use cond::cache::ArmCache;
+    /// use cond::{cond_static, StaticCond};
+    ///
+    /// cond_static! {
+    ///     static PARITY: StaticCond<u64, &'static str> = {
+    ///         |n| n % 2 == 0 => |_| "even",
+    ///         _ => |_| "odd",
+    ///     };
+    /// }
+    ///
+    /// let mut cache = ArmCache::new(16);
+    /// for n in [1, 2, 1, 2] {
+    ///     PARITY.eval_cached(&n, &mut cache);
+    /// }
+    /// assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    /// ```
+    pub fn eval_cached(&self, input: &I, cache: &mut crate::cache::ArmCache<I>) -> Option<T> {
+        cache.tie_to(self.arms.as_ptr() as usize);
+        match cache.arm(input.clone(), |input| self.position(input)) {
+            Some(index) => Some((self.arms[index].handler)(input)),
+            None => self.default.map(|default| default(input)),
+        }
+    }
+}
+
+impl<I: ?Sized, T> fmt::Debug for StaticCond<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticCond")
+            .field("arms", &self.arms)
+            .field("has_default", &self.has_default())
+            .finish()
+    }
+}
+
+impl<I: ?Sized, T> fmt::Debug for StaticArm<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticArm")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,520 @@
+//! Dispatch on CPU features.
+
+#[macro_export]
+/// Selects a value, typically a function implementing a kernel, by the first CPU feature that is
+/// available, like a [`cond!`](crate::cond) table whose conditions are feature checks.
+///
+/// Each arm is a feature name string, as accepted by `#[target_feature(enable = ...)]`, followed by
+/// `=>` and a value. A default arm is required. A feature enabled at compile time always counts as
+/// available, so the check costs nothing; otherwise, with the `std` feature, the CPU is queried
+/// with [`is_x86_feature_detected!`](std::arch::is_x86_feature_detected) on x86 and x86-64, and
+/// with `is_aarch64_feature_detected!` on AArch64. The features of other architectures are never
+/// detected, so one table can list the kernels of several, and neither are features those macros
+/// do not know. On other targets, or without `std`, only features enabled at compile time are
+/// available. A name that is not a feature of any architecture, such as a misspelled one, is a
+/// compile error.
+///
+/// The detection runs the first time an invocation is evaluated, and the arm it picks is cached
+/// for the rest of the program. The arm's value is still evaluated each time.
+///
+/// ```
+/// # use cond::cond_target_feature;
+/// fn sum_avx2(xs: &[f32]) -> f32 { xs.iter().sum() }
+/// fn sum_sse2(xs: &[f32]) -> f32 { xs.iter().sum() }
+/// fn sum_scalar(xs: &[f32]) -> f32 { xs.iter().sum() }
+///
+/// let sum: fn(&[f32]) -> f32 = cond_target_feature! {
+///     "avx2" => sum_avx2,
+///     "sse2" => sum_sse2,
+///     _ => sum_scalar,
+/// };
+/// assert_eq!(sum(&[1.0, 2.0]), 3.0);
+/// ```
+///
+/// ```compile_fail
+/// # use cond::cond_target_feature;
+/// let wide = cond_target_feature! { "avx-2" => true, _ => false };
+/// ```
+macro_rules! cond_target_feature {
+    // The feature names are matched as token trees, which the detection macros need, so the
+    // arms are munched one at a time to tell them apart from the default arm.
+    (@arms [$(($feature:tt $value:expr))+] _ => $default:expr $(,)?) => {{
+        static CHOSEN: ::core::sync::atomic::AtomicUsize = ::core::sync::atomic::AtomicUsize::new(0);
+        let chosen = match CHOSEN.load(::core::sync::atomic::Ordering::Relaxed) {
+            0 => {
+                let chosen = 'detect: {
+                    let mut arm = 0;
+                    $(
+                        if ::core::cfg!(target_feature = $feature)
+                            || $crate::__target_feature_detected!($feature)
+                        {
+                            break 'detect arm;
+                        }
+                        arm += 1;
+                    )+
+                    arm
+                };
+                // 0 means not detected yet, so arms are stored one higher.
+                CHOSEN.store(chosen + 1, ::core::sync::atomic::Ordering::Relaxed);
+                chosen
+            }
+            stored => stored - 1,
+        };
+        let mut arm = 0;
+        $(if { arm += 1; arm - 1 == chosen } { $value } else)+ { $default }
+    }};
+    (@arms [$($arms:tt)*] $feature:tt => $value:expr, $($rest:tt)*) => {
+        $crate::cond_target_feature! { @arms [$($arms)* ($feature $value)] $($rest)* }
+    };
+    ($($arms:tt)+) => {
+        $crate::cond_target_feature! { @arms [] $($arms)+ }
+    };
+}
+
+// Each detection macro rejects the names it does not know, so only the names of its architecture's
+// features are passed to it.
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __target_feature_detected {
+    ("aes") => {
+        $crate::__private::is_x86_feature_detected!("aes")
+    };
+    ("pclmulqdq") => {
+        $crate::__private::is_x86_feature_detected!("pclmulqdq")
+    };
+    ("rdrand") => {
+        $crate::__private::is_x86_feature_detected!("rdrand")
+    };
+    ("rdseed") => {
+        $crate::__private::is_x86_feature_detected!("rdseed")
+    };
+    ("tsc") => {
+        $crate::__private::is_x86_feature_detected!("tsc")
+    };
+    ("mmx") => {
+        $crate::__private::is_x86_feature_detected!("mmx")
+    };
+    ("sse") => {
+        $crate::__private::is_x86_feature_detected!("sse")
+    };
+    ("sse2") => {
+        $crate::__private::is_x86_feature_detected!("sse2")
+    };
+    ("sse3") => {
+        $crate::__private::is_x86_feature_detected!("sse3")
+    };
+    ("ssse3") => {
+        $crate::__private::is_x86_feature_detected!("ssse3")
+    };
+    ("sse4.1") => {
+        $crate::__private::is_x86_feature_detected!("sse4.1")
+    };
+    ("sse4.2") => {
+        $crate::__private::is_x86_feature_detected!("sse4.2")
+    };
+    ("sse4a") => {
+        $crate::__private::is_x86_feature_detected!("sse4a")
+    };
+    ("sha") => {
+        $crate::__private::is_x86_feature_detected!("sha")
+    };
+    ("avx") => {
+        $crate::__private::is_x86_feature_detected!("avx")
+    };
+    ("avx2") => {
+        $crate::__private::is_x86_feature_detected!("avx2")
+    };
+    ("avx512f") => {
+        $crate::__private::is_x86_feature_detected!("avx512f")
+    };
+    ("avx512cd") => {
+        $crate::__private::is_x86_feature_detected!("avx512cd")
+    };
+    ("avx512bw") => {
+        $crate::__private::is_x86_feature_detected!("avx512bw")
+    };
+    ("avx512dq") => {
+        $crate::__private::is_x86_feature_detected!("avx512dq")
+    };
+    ("avx512vl") => {
+        $crate::__private::is_x86_feature_detected!("avx512vl")
+    };
+    ("avx512ifma") => {
+        $crate::__private::is_x86_feature_detected!("avx512ifma")
+    };
+    ("avx512vbmi") => {
+        $crate::__private::is_x86_feature_detected!("avx512vbmi")
+    };
+    ("avx512vpopcntdq") => {
+        $crate::__private::is_x86_feature_detected!("avx512vpopcntdq")
+    };
+    ("avx512vbmi2") => {
+        $crate::__private::is_x86_feature_detected!("avx512vbmi2")
+    };
+    ("gfni") => {
+        $crate::__private::is_x86_feature_detected!("gfni")
+    };
+    ("vaes") => {
+        $crate::__private::is_x86_feature_detected!("vaes")
+    };
+    ("vpclmulqdq") => {
+        $crate::__private::is_x86_feature_detected!("vpclmulqdq")
+    };
+    ("avx512vnni") => {
+        $crate::__private::is_x86_feature_detected!("avx512vnni")
+    };
+    ("avx512bitalg") => {
+        $crate::__private::is_x86_feature_detected!("avx512bitalg")
+    };
+    ("avx512bf16") => {
+        $crate::__private::is_x86_feature_detected!("avx512bf16")
+    };
+    ("avx512vp2intersect") => {
+        $crate::__private::is_x86_feature_detected!("avx512vp2intersect")
+    };
+    ("avx512fp16") => {
+        $crate::__private::is_x86_feature_detected!("avx512fp16")
+    };
+    ("f16c") => {
+        $crate::__private::is_x86_feature_detected!("f16c")
+    };
+    ("fma") => {
+        $crate::__private::is_x86_feature_detected!("fma")
+    };
+    ("bmi1") => {
+        $crate::__private::is_x86_feature_detected!("bmi1")
+    };
+    ("bmi2") => {
+        $crate::__private::is_x86_feature_detected!("bmi2")
+    };
+    ("abm") => {
+        $crate::__private::is_x86_feature_detected!("abm")
+    };
+    ("lzcnt") => {
+        $crate::__private::is_x86_feature_detected!("lzcnt")
+    };
+    ("tbm") => {
+        $crate::__private::is_x86_feature_detected!("tbm")
+    };
+    ("popcnt") => {
+        $crate::__private::is_x86_feature_detected!("popcnt")
+    };
+    ("fxsr") => {
+        $crate::__private::is_x86_feature_detected!("fxsr")
+    };
+    ("xsave") => {
+        $crate::__private::is_x86_feature_detected!("xsave")
+    };
+    ("xsaveopt") => {
+        $crate::__private::is_x86_feature_detected!("xsaveopt")
+    };
+    ("xsaves") => {
+        $crate::__private::is_x86_feature_detected!("xsaves")
+    };
+    ("xsavec") => {
+        $crate::__private::is_x86_feature_detected!("xsavec")
+    };
+    ("cmpxchg16b") => {
+        $crate::__private::is_x86_feature_detected!("cmpxchg16b")
+    };
+    ("adx") => {
+        $crate::__private::is_x86_feature_detected!("adx")
+    };
+    ("rtm") => {
+        $crate::__private::is_x86_feature_detected!("rtm")
+    };
+    ("movbe") => {
+        $crate::__private::is_x86_feature_detected!("movbe")
+    };
+    ("ermsb") => {
+        $crate::__private::is_x86_feature_detected!("ermsb")
+    };
+    ($feature:tt) => {
+        $crate::__undetected_target_feature!($feature)
+    };
+}
+
+#[cfg(all(feature = "std", target_arch = "aarch64"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __target_feature_detected {
+    ("neon") => {
+        $crate::__private::is_aarch64_feature_detected!("neon")
+    };
+    ("asimd") => {
+        $crate::__private::is_aarch64_feature_detected!("asimd")
+    };
+    ("pmull") => {
+        $crate::__private::is_aarch64_feature_detected!("pmull")
+    };
+    ("fp") => {
+        $crate::__private::is_aarch64_feature_detected!("fp")
+    };
+    ("fp16") => {
+        $crate::__private::is_aarch64_feature_detected!("fp16")
+    };
+    ("sve") => {
+        $crate::__private::is_aarch64_feature_detected!("sve")
+    };
+    ("crc") => {
+        $crate::__private::is_aarch64_feature_detected!("crc")
+    };
+    ("lse") => {
+        $crate::__private::is_aarch64_feature_detected!("lse")
+    };
+    ("lse2") => {
+        $crate::__private::is_aarch64_feature_detected!("lse2")
+    };
+    ("rdm") => {
+        $crate::__private::is_aarch64_feature_detected!("rdm")
+    };
+    ("rcpc") => {
+        $crate::__private::is_aarch64_feature_detected!("rcpc")
+    };
+    ("rcpc2") => {
+        $crate::__private::is_aarch64_feature_detected!("rcpc2")
+    };
+    ("dotprod") => {
+        $crate::__private::is_aarch64_feature_detected!("dotprod")
+    };
+    ("tme") => {
+        $crate::__private::is_aarch64_feature_detected!("tme")
+    };
+    ("fhm") => {
+        $crate::__private::is_aarch64_feature_detected!("fhm")
+    };
+    ("dit") => {
+        $crate::__private::is_aarch64_feature_detected!("dit")
+    };
+    ("flagm") => {
+        $crate::__private::is_aarch64_feature_detected!("flagm")
+    };
+    ("ssbs") => {
+        $crate::__private::is_aarch64_feature_detected!("ssbs")
+    };
+    ("sb") => {
+        $crate::__private::is_aarch64_feature_detected!("sb")
+    };
+    ("paca") => {
+        $crate::__private::is_aarch64_feature_detected!("paca")
+    };
+    ("pacg") => {
+        $crate::__private::is_aarch64_feature_detected!("pacg")
+    };
+    ("dpb") => {
+        $crate::__private::is_aarch64_feature_detected!("dpb")
+    };
+    ("dpb2") => {
+        $crate::__private::is_aarch64_feature_detected!("dpb2")
+    };
+    ("sve2") => {
+        $crate::__private::is_aarch64_feature_detected!("sve2")
+    };
+    ("sve2-aes") => {
+        $crate::__private::is_aarch64_feature_detected!("sve2-aes")
+    };
+    ("sve2-sm4") => {
+        $crate::__private::is_aarch64_feature_detected!("sve2-sm4")
+    };
+    ("sve2-sha3") => {
+        $crate::__private::is_aarch64_feature_detected!("sve2-sha3")
+    };
+    ("sve2-bitperm") => {
+        $crate::__private::is_aarch64_feature_detected!("sve2-bitperm")
+    };
+    ("frintts") => {
+        $crate::__private::is_aarch64_feature_detected!("frintts")
+    };
+    ("i8mm") => {
+        $crate::__private::is_aarch64_feature_detected!("i8mm")
+    };
+    ("f32mm") => {
+        $crate::__private::is_aarch64_feature_detected!("f32mm")
+    };
+    ("f64mm") => {
+        $crate::__private::is_aarch64_feature_detected!("f64mm")
+    };
+    ("bf16") => {
+        $crate::__private::is_aarch64_feature_detected!("bf16")
+    };
+    ("rand") => {
+        $crate::__private::is_aarch64_feature_detected!("rand")
+    };
+    ("bti") => {
+        $crate::__private::is_aarch64_feature_detected!("bti")
+    };
+    ("mte") => {
+        $crate::__private::is_aarch64_feature_detected!("mte")
+    };
+    ("jsconv") => {
+        $crate::__private::is_aarch64_feature_detected!("jsconv")
+    };
+    ("fcma") => {
+        $crate::__private::is_aarch64_feature_detected!("fcma")
+    };
+    ("aes") => {
+        $crate::__private::is_aarch64_feature_detected!("aes")
+    };
+    ("sha2") => {
+        $crate::__private::is_aarch64_feature_detected!("sha2")
+    };
+    ("sha3") => {
+        $crate::__private::is_aarch64_feature_detected!("sha3")
+    };
+    ("sm4") => {
+        $crate::__private::is_aarch64_feature_detected!("sm4")
+    };
+    ($feature:tt) => {
+        $crate::__undetected_target_feature!($feature)
+    };
+}
+
+#[cfg(not(all(
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __target_feature_detected {
+    ($feature:tt) => {
+        $crate::__undetected_target_feature!($feature)
+    };
+}
+
+// A feature not detected at runtime is only available when enabled at compile time, but a name
+// that is not a feature of any architecture is most likely misspelled.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __undetected_target_feature {
+    ($feature:tt) => {{
+        const _: () = ::core::assert!(
+            $crate::__private::is_target_feature($feature),
+            ::core::concat!(
+                "`",
+                $feature,
+                "` is not a target feature of any architecture"
+            )
+        );
+        false
+    }};
+}
+
+/// Returns whether `name` is one of [`TARGET_FEATURES`].
+#[doc(hidden)]
+pub const fn is_target_feature(name: &str) -> bool {
+    let mut i = 0;
+    while i < TARGET_FEATURES.len() {
+        let (known, name) = (TARGET_FEATURES[i].as_bytes(), name.as_bytes());
+        if known.len() == name.len() {
+            let mut j = 0;
+            while j < known.len() && known[j] == name[j] {
+                j += 1;
+            }
+            if j == known.len() {
+                return true;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+/// The target features rustc knows of, by architecture.
+#[rustfmt::skip]
+const TARGET_FEATURES: &[&str] = &[
+    // x86 and x86-64
+    "adx", "aes", "amx-avx512", "amx-bf16", "amx-complex", "amx-fp16", "amx-fp8", "amx-int8",
+    "amx-movrs", "amx-tf32", "amx-tile", "apxf", "avx", "avx10.1", "avx10.2", "avx2",
+    "avx512bf16", "avx512bitalg", "avx512bw", "avx512cd", "avx512dq", "avx512f", "avx512fp16",
+    "avx512ifma", "avx512vbmi", "avx512vbmi2", "avx512vl", "avx512vnni", "avx512vp2intersect",
+    "avx512vpopcntdq", "avxifma", "avxneconvert", "avxvnni", "avxvnniint16", "avxvnniint8",
+    "bmi1", "bmi2", "cmpxchg16b", "crt-static", "ermsb", "f16c", "fma", "fxsr", "gfni", "kl",
+    "lahfsahf", "lzcnt", "movbe", "movrs", "pclmulqdq", "popcnt", "prfchw", "rdrand", "rdseed",
+    "rtm", "sha", "sha512", "sm3", "sm4", "sse", "sse2", "sse3", "sse4.1", "sse4.2", "sse4a",
+    "ssse3", "tbm", "vaes", "vpclmulqdq", "widekl", "x87", "xop", "xsave", "xsavec", "xsaveopt",
+    "xsaves",
+    // AArch64
+    "aes", "bf16", "bti", "crc", "crt-static", "cssc", "dit", "dotprod", "dpb", "dpb2", "ecv",
+    "f32mm", "f64mm", "faminmax", "fcma", "fhm", "flagm", "flagm2", "fp16", "fp8", "fp8dot2",
+    "fp8dot4", "fp8fma", "frintts", "hbc", "i8mm", "jsconv", "lor", "lse", "lse128", "lse2",
+    "lut", "mops", "mte", "neon", "outline-atomics", "paca", "pacg", "pan", "pauth-lr", "pmuv3",
+    "rand", "ras", "rcpc", "rcpc2", "rcpc3", "rdm", "sb", "sha2", "sha3", "sm4", "sme",
+    "sme-b16b16", "sme-f16f16", "sme-f64f64", "sme-f8f16", "sme-f8f32", "sme-fa64",
+    "sme-i16i64", "sme-lutv2", "sme2", "sme2p1", "spe", "ssbs", "ssve-fp8dot2", "ssve-fp8dot4",
+    "ssve-fp8fma", "sve", "sve-b16b16", "sve2", "sve2-aes", "sve2-bitperm", "sve2-sha3",
+    "sve2-sm4", "sve2p1", "v8.1a", "v8.2a", "v8.3a", "v8.4a", "v8.5a", "v8.6a", "v8.7a",
+    "v8.8a", "v8.9a", "v9.1a", "v9.2a", "v9.3a", "v9.4a", "v9.5a", "v9a", "vh", "wfxt",
+    // Arm
+    "aclass", "aes", "crc", "crt-static", "d32", "dotprod", "dsp", "fp-armv8", "fp16", "fpregs",
+    "i8mm", "mclass", "neon", "rclass", "sha2", "soft-float", "thumb-mode", "thumb2",
+    "trustzone", "v5te", "v6", "v6k", "v6t2", "v7", "v8", "vfp2", "vfp3", "vfp4",
+    "virtualization",
+    // RISC-V
+    "a", "b", "c", "crt-static", "d", "e", "f", "m", "relax", "rva23u64", "supm",
+    "unaligned-scalar-mem", "unaligned-vector-mem", "v", "za128rs", "za64rs", "zaamo", "zabha",
+    "zacas", "zalrsc", "zama16b", "zawrs", "zba", "zbb", "zbc", "zbkb", "zbkc", "zbkx", "zbs",
+    "zca", "zcb", "zcmop", "zdinx", "zfa", "zfbfmin", "zfh", "zfhmin", "zfinx", "zhinx",
+    "zhinxmin", "zic64b", "zicbom", "zicbop", "zicboz", "ziccamoa", "ziccif", "zicclsm",
+    "ziccrse", "zicntr", "zicond", "zicsr", "zifencei", "zihintntl", "zihintpause", "zihpm",
+    "zimop", "zk", "zkn", "zknd", "zkne", "zknh", "zkr", "zks", "zksed", "zksh", "zkt", "ztso",
+    "zvbb", "zvbc", "zve32f", "zve32x", "zve64d", "zve64f", "zve64x", "zvfbfmin", "zvfbfwma",
+    "zvfh", "zvfhmin", "zvkb", "zvkg", "zvkn", "zvknc", "zvkned", "zvkng", "zvknha", "zvknhb",
+    "zvks", "zvksc", "zvksed", "zvksg", "zvksh", "zvkt", "zvl1024b", "zvl128b", "zvl16384b",
+    "zvl2048b", "zvl256b", "zvl32768b", "zvl32b", "zvl4096b", "zvl512b", "zvl64b", "zvl65536b",
+    "zvl8192b",
+    // WebAssembly
+    "atomics", "bulk-memory", "crt-static", "exception-handling", "extended-const", "gc",
+    "multivalue", "mutable-globals", "nontrapping-fptoint", "reference-types", "relaxed-simd",
+    "sign-ext", "simd128", "tail-call", "wide-arithmetic",
+    // PowerPC
+    "altivec", "crt-static", "msync", "partword-atomics", "power10-vector", "power8-altivec",
+    "power8-crypto", "power8-vector", "power9-altivec", "power9-vector", "quadword-atomics",
+    "vsx",
+    // LoongArch
+    "32s", "crt-static", "d", "div32", "f", "frecipe", "lam-bh", "lamcas", "lasx", "lbt",
+    "ld-seq-sa", "lsx", "lvz", "relax", "scq", "ual",
+    // s390x
+    "backchain", "concurrent-functions", "crt-static", "deflate-conversion", "enhanced-sort",
+    "guarded-storage", "high-word", "message-security-assist-extension12",
+    "message-security-assist-extension3", "message-security-assist-extension4",
+    "message-security-assist-extension5", "message-security-assist-extension8",
+    "message-security-assist-extension9", "miscellaneous-extensions-2",
+    "miscellaneous-extensions-3", "miscellaneous-extensions-4", "nnp-assist",
+    "transactional-execution", "vector", "vector-enhancements-1", "vector-enhancements-2",
+    "vector-enhancements-3", "vector-packed-decimal", "vector-packed-decimal-enhancement",
+    "vector-packed-decimal-enhancement-2", "vector-packed-decimal-enhancement-3",
+    // MIPS
+    "crt-static", "fp64", "msa", "virt",
+    // Hexagon
+    "crt-static", "hvx", "hvx-ieee-fp", "hvx-length128b", "hvx-length64b", "hvx-qfloat",
+    "hvxv60", "hvxv62", "hvxv65", "hvxv66", "hvxv67", "hvxv68", "hvxv69", "hvxv71", "hvxv73",
+    "hvxv75", "hvxv79", "zreg",
+    // SPARC
+    "crt-static", "leoncasa", "v8plus", "v9",
+    // NVPTX
+    "crt-static", "ptx32", "ptx40", "ptx41", "ptx42", "ptx43", "ptx50", "ptx60", "ptx61",
+    "ptx62", "ptx63", "ptx64", "ptx65", "ptx70", "ptx71", "ptx72", "ptx73", "ptx74", "ptx75",
+    "ptx76", "ptx77", "ptx78", "ptx80", "ptx81", "ptx82", "ptx83", "ptx84", "ptx85", "ptx86",
+    "ptx87", "sm_100", "sm_100a", "sm_101", "sm_101a", "sm_120", "sm_120a", "sm_20", "sm_21",
+    "sm_30", "sm_32", "sm_35", "sm_37", "sm_50", "sm_52", "sm_53", "sm_60", "sm_61", "sm_62",
+    "sm_70", "sm_72", "sm_75", "sm_80", "sm_86", "sm_87", "sm_89", "sm_90", "sm_90a",
+    // C-SKY
+    "10e60", "2e3", "3e3r1", "3e3r2", "3e3r3", "3e7", "7e10", "cache", "crt-static", "doloop",
+    "dsp1e2", "dspe60", "e1", "e2", "edsp", "elrw", "fdivdu", "float1e2", "float1e3",
+    "float3e4", "float7e60", "floate1", "fpuv2_df", "fpuv2_sf", "fpuv3_df", "fpuv3_hf",
+    "fpuv3_hi", "fpuv3_sf", "hard-float", "hard-float-abi", "hard-tp", "high-registers",
+    "hwdiv", "mp", "mp1e2", "nvic", "trust", "vdsp2e60f", "vdspv1", "vdspv2",
+    // M68k
+    "crt-static", "isa-68000", "isa-68010", "isa-68020", "isa-68030", "isa-68040", "isa-68060",
+    "isa-68881", "isa-68882",
+    // AVR
+    "addsubiw", "break", "crt-static", "eijmpcall", "elpm", "elpmx", "ijmpcall", "jmpcall",
+    "lowbytefirst", "lpm", "lpmx", "movw", "mul", "rmw", "spm", "spmx", "tinyencoding",
+    // BPF
+    "alu32", "crt-static",
+    // Xtensa
+    "crt-static",
+    // AMDGPU
+    "crt-static",
+];
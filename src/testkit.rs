@@ -0,0 +1,562 @@
+//! Helpers for checking that tests actually reach every arm of a decision table.
+//!
+//! A decision table is described to this module by the names of its arms and a selector: a
+//! function from an input to the index of the arm it picks, with the default arm counted last.
+//! [`ArmSearch`] then feeds it inputs from a [`Strategy`] until every arm has been hit or the
+//! attempt budget is spent, and reports which arms it could not reach.
+//!
+//! ```
+//! use cond::cond;
+//! use cond::testkit::ArmSearch;
+//!
+//! fn sign(x: i64) -> usize {
+//!     cond! {
+//!         x < 0 => 0,
+//!         x == 0 => 1,
+//!         _ => 2,
+//!     }
+//! }
+//!
+//! let coverage = ArmSearch::new(&["negative", "zero", "positive"])
+//!     .run(|rng: &mut cond::testkit::Rng| rng.range_i64(-5..5), |x| sign(*x));
+//! coverage.assert_complete();
+//! assert_eq!(coverage.witness(1), Some(&0));
+//! ```
+//!
+//! Arms whose conditions are hard to make hold in a test, like a full disk, can instead be forced
+//! on [`cond_named!`](crate::cond_named) tables with [`force`].
+//!
+//! To guard a table against accidental changes, [`snapshot`] renders its decisions over a corpus
+//! as text, and [`assert_snapshot`] compares them against a checked-in golden file.
+
+use core::fmt::{Debug, Write as _};
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+use std::{format, vec};
+
+/// A small, deterministic pseudo-random number generator (SplitMix64) for input strategies.
+///
+/// It is not suitable for anything but generating test inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from a seed. The same seed always yields the same sequence.
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random boolean.
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Returns a random integer in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn range_i64(&mut self, range: Range<i64>) -> i64 {
+        assert!(range.start < range.end, "cannot sample from an empty range");
+        let width = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % width) as i64)
+    }
+
+    /// Returns a random float in `[0, 1)`.
+    pub fn f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A source of inputs for [`ArmSearch`].
+///
+/// Closures taking a [`Rng`] are random strategies; [`Corpus`] wraps a fixed, user-provided list.
+pub trait Strategy<I> {
+    /// Produces the next input, or `None` when the strategy is exhausted.
+    fn next_input(&mut self, rng: &mut Rng) -> Option<I>;
+}
+
+impl<I, F: FnMut(&mut Rng) -> I> Strategy<I> for F {
+    fn next_input(&mut self, rng: &mut Rng) -> Option<I> {
+        Some(self(rng))
+    }
+}
+
+/// A strategy that yields the inputs of an iterator in order.
+#[derive(Debug, Clone)]
+pub struct Corpus<T>(T);
+
+impl<T: Iterator> Corpus<T> {
+    /// Wraps anything iterable as a strategy.
+    pub fn new(inputs: impl IntoIterator<IntoIter = T>) -> Self {
+        Corpus(inputs.into_iter())
+    }
+}
+
+impl<T: Iterator> Strategy<T::Item> for Corpus<T> {
+    fn next_input(&mut self, _: &mut Rng) -> Option<T::Item> {
+        self.0.next()
+    }
+}
+
+/// Searches for inputs that select each arm of a decision table.
+#[derive(Debug, Clone)]
+pub struct ArmSearch<'a> {
+    arms: &'a [&'a str],
+    attempts: usize,
+    seed: u64,
+}
+
+impl<'a> ArmSearch<'a> {
+    /// Creates a search over a table whose arms are described by `arms`, in order.
+    pub fn new(arms: &'a [&'a str]) -> Self {
+        ArmSearch {
+            arms,
+            attempts: 10_000,
+            seed: 0,
+        }
+    }
+
+    /// Sets how many inputs are tried before giving up. Defaults to 10,000.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Sets the seed passed to random strategies. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the search, recording the first input found for each arm.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `select` returns an index that is not a valid arm.
+    pub fn run<I, S, F>(&self, mut strategy: S, select: F) -> Coverage<'a, I>
+    where
+        S: Strategy<I>,
+        F: Fn(&I) -> usize,
+    {
+        let mut rng = Rng::new(self.seed);
+        let mut witnesses: Vec<Option<I>> = (0..self.arms.len()).map(|_| None).collect();
+        let mut remaining = self.arms.len();
+        for _ in 0..self.attempts {
+            if remaining == 0 {
+                break;
+            }
+            let Some(input) = strategy.next_input(&mut rng) else {
+                break;
+            };
+            let arm = select(&input);
+            let slot = witnesses.get_mut(arm).unwrap_or_else(|| {
+                panic!(
+                    "selector returned arm {arm}, but the table has {} arms",
+                    self.arms.len()
+                )
+            });
+            if slot.is_none() {
+                *slot = Some(input);
+                remaining -= 1;
+            }
+        }
+        Coverage {
+            arms: self.arms,
+            witnesses,
+        }
+    }
+}
+
+/// The outcome of an [`ArmSearch`].
+#[derive(Debug, Clone)]
+pub struct Coverage<'a, I> {
+    arms: &'a [&'a str],
+    witnesses: Vec<Option<I>>,
+}
+
+impl<I> Coverage<'_, I> {
+    /// Returns the first input found that selects `arm`, if any.
+    pub fn witness(&self, arm: usize) -> Option<&I> {
+        self.witnesses.get(arm)?.as_ref()
+    }
+
+    /// Returns the names of the arms no input selected.
+    pub fn unreached(&self) -> Vec<&str> {
+        self.arms
+            .iter()
+            .zip(&self.witnesses)
+            .filter(|(_, witness)| witness.is_none())
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    /// Returns whether every arm was selected at least once.
+    pub fn is_complete(&self) -> bool {
+        self.witnesses.iter().all(Option::is_some)
+    }
+
+    /// Panics with the names of the unreached arms unless every arm was selected.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        assert!(
+            self.is_complete(),
+            "no input reached arms {:?}",
+            self.unreached()
+        );
+    }
+}
+
+/// Compares two implementations of a decision table, such as the original and a refactored one,
+/// over the same inputs.
+///
+/// The implementations are functions from an input to its outcome, typically the index of the
+/// selected arm or the value the table produces.
+///
+/// ```
+/// use cond::cond;
+/// use cond::testkit::{Differential, Rng};
+///
+/// fn old(age: &i64) -> &'static str {
+///     cond! {
+///         *age < 13 => "child",
+///         *age < 18 => "teen",
+///         _ => "adult",
+///     }
+/// }
+/// fn new(age: &i64) -> &'static str {
+///     cond! {
+///         *age >= 18 => "adult",
+///         *age > 13 => "teen",
+///         _ => "child",
+///     }
+/// }
+///
+/// let differences = Differential::new().run(|rng: &mut Rng| rng.range_i64(0..30), old, new);
+/// assert_eq!(differences.first().map(|d| (d.input, d.old, d.new)), Some((13, "teen", "child")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Differential {
+    attempts: usize,
+    seed: u64,
+}
+
+impl Differential {
+    /// Creates a comparison with the default settings.
+    pub fn new() -> Self {
+        Differential {
+            attempts: 10_000,
+            seed: 0,
+        }
+    }
+
+    /// Sets how many inputs are compared. Defaults to 10,000.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Sets the seed passed to random strategies. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs both implementations on every input, and returns the inputs where their outcomes
+    /// differ, in the order they were generated.
+    pub fn run<I, O, S, A, B>(&self, mut strategy: S, old: A, new: B) -> Vec<Difference<I, O>>
+    where
+        O: PartialEq,
+        S: Strategy<I>,
+        A: Fn(&I) -> O,
+        B: Fn(&I) -> O,
+    {
+        let mut rng = Rng::new(self.seed);
+        let mut differences = Vec::new();
+        for _ in 0..self.attempts {
+            let Some(input) = strategy.next_input(&mut rng) else {
+                break;
+            };
+            let (old, new) = (old(&input), new(&input));
+            if old != new {
+                differences.push(Difference { input, old, new });
+            }
+        }
+        differences
+    }
+}
+
+impl Default for Differential {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An input on which the implementations compared by a [`Differential`] disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference<I, O> {
+    /// The input.
+    pub input: I,
+    /// The outcome of the first implementation.
+    pub old: O,
+    /// The outcome of the second implementation.
+    pub new: O,
+}
+
+/// The environment variable that makes [`assert_snapshot`] write snapshots instead of comparing
+/// against them, when set to anything but `0`.
+pub const UPDATE_SNAPSHOTS: &str = "COND_UPDATE_SNAPSHOTS";
+
+/// Renders the outcomes of a decision table over a corpus of inputs as text, one line per input
+/// in the form `input => outcome`, both written with [`Debug`].
+///
+/// The text only depends on the inputs and their outcomes, so it can be checked in as a golden
+/// file and compared with [`assert_snapshot`].
+pub fn snapshot<I, O, F>(inputs: impl IntoIterator<Item = I>, decide: F) -> String
+where
+    I: Debug,
+    O: Debug,
+    F: Fn(&I) -> O,
+{
+    let mut text = String::new();
+    for input in inputs {
+        let outcome = decide(&input);
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(text, "{input:?} => {outcome:?}");
+    }
+    text
+}
+
+/// Compares `actual`, usually made with [`snapshot`], against the snapshot stored at `path`, and
+/// panics with a line diff if they differ or if there is no snapshot.
+///
+/// With the [`UPDATE_SNAPSHOTS`] environment variable set, the snapshot is written instead, so
+/// after an intended change, `COND_UPDATE_SNAPSHOTS=1 cargo test` updates every snapshot. A
+/// relative `path` is relative to the working directory, which `cargo test` sets to the root of
+/// the package.
+///
+/// ```
+/// use cond::cond;
+/// use cond::testkit::{assert_snapshot, snapshot};
+///
+/// fn tier(spend: u32) -> &'static str {
+///     cond! {
+///         spend >= 1000 => "gold",
+///         spend >= 100 => "silver",
+///         _ => "bronze",
+///     }
+/// }
+///
+/// let path = std::env::temp_dir().join("cond-doc-tier.snap");
+/// # std::fs::write(&path, "0 => \"bronze\"\n150 => \"silver\"\n5000 => \"gold\"\n").unwrap();
+/// assert_snapshot(&path, &snapshot([0, 150, 5000], |&spend| tier(spend)));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the snapshot differs from `actual`, does not exist, or cannot be read or written.
+#[track_caller]
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let update = std::env::var_os(UPDATE_SNAPSHOTS).is_some_and(|value| value != "0");
+    if let Err(message) = compare_snapshot(path.as_ref(), actual, update) {
+        panic!("{message}");
+    }
+}
+
+/// Compares `actual` against the snapshot at `path`, or writes it there if `update` is set.
+fn compare_snapshot(path: &Path, actual: &str, update: bool) -> Result<(), String> {
+    let shown = path.display();
+    if update {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|error| format!("cannot create the directory of {shown}: {error}"))?;
+        }
+        return std::fs::write(path, actual)
+            .map_err(|error| format!("cannot write the snapshot {shown}: {error}"));
+    }
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err(format!(
+                "there is no snapshot at {shown}; set {UPDATE_SNAPSHOTS}=1 to create it"
+            ))
+        }
+        Err(error) => return Err(format!("cannot read the snapshot {shown}: {error}")),
+    };
+    if expected == actual {
+        return Ok(());
+    }
+    Err(format!(
+        "the decisions differ from the snapshot {shown} (-snapshot +actual); set \
+         {UPDATE_SNAPSHOTS}=1 to accept them:\n{}",
+        line_diff(&expected, actual)
+    ))
+}
+
+/// Lists the lines removed from `old` and added in `new`, along a longest common subsequence, so
+/// that an input added to or removed from the corpus shows up as a single line.
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // `common[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`.
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            let _ = writeln!(diff, "{:>5} -{}", i + 1, old[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "{:>5} +{}", j + 1, new[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[macro_export]
+/// Generates one `#[test]` function per arm of a decision table, each checking that a sample
+/// input selects that arm.
+///
+/// The first argument is the table, as a function or closure from an input to its outcome,
+/// followed by a semicolon. Each case is then `test_name: input => expected_outcome`, and the
+/// generated test asserts that the table maps `input` to `expected_outcome`. Invoke it where the
+/// tests should go, such as in a `#[cfg(test)]` module.
+///
+/// ```
+/// # use cond::cond;
+/// fn shipping(weight: u32) -> &'static str {
+///     cond! {
+///         weight == 0 => "free",
+///         weight < 20 => "standard",
+///         _ => "freight",
+///     }
+/// }
+///
+/// #[cfg(test)]
+/// mod tests {
+///     cond::cond_cases! { super::shipping;
+///         empty_parcels_ship_free: 0 => "free",
+///         light_parcels_ship_standard: 5 => "standard",
+///         heavy_parcels_ship_as_freight: 20 => "freight",
+///     }
+/// }
+/// ```
+macro_rules! cond_cases {
+    ($table:expr; $($name:ident : $input:expr => $expected:expr),+ $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                ::core::assert_eq!(($table)($input), $expected, "input: {}", ::core::stringify!($input));
+            }
+        )+
+    };
+}
+
+std::thread_local! {
+    /// The active overrides of the current thread, innermost last, each with the id of its guard.
+    static OVERRIDES: RefCell<Vec<(u64, String, Option<String>)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Forces the [`cond_named!`](crate::cond_named) table named `site` to select the arm named `arm`
+/// on the current thread, without evaluating any condition, until the returned guard is dropped.
+///
+/// Overrides nest: while a later override of the same table is alive, it takes precedence. An
+/// evaluation panics if the forced arm does not exist in the table.
+///
+/// Overrides only apply in tests and in builds with debug assertions. Elsewhere, `cond_named!`
+/// tables skip looking for them, even with the `testkit` feature enabled, which otherwise costs a
+/// thread-local lookup on every evaluation.
+///
+/// ```
+/// # use cond::cond_named;
+/// use cond::testkit;
+///
+/// fn save() -> &'static str {
+///     let free_bytes = 1 << 30;
+///     cond_named! { "save";
+///         "disk_full": free_bytes == 0 => "error: disk full",
+///         _ => "saved",
+///     }
+/// }
+///
+/// {
+///     let _guard = testkit::force("save", "disk_full");
+///     assert_eq!(save(), "error: disk full");
+/// }
+/// assert_eq!(save(), "saved");
+/// ```
+pub fn force(site: &str, arm: &str) -> Override {
+    push_override(site, Some(arm.into()))
+}
+
+/// Like [`force`], but forces the default arm.
+pub fn force_default(site: &str) -> Override {
+    push_override(site, None)
+}
+
+fn push_override(site: &str, arm: Option<String>) -> Override {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    OVERRIDES.with(|overrides| overrides.borrow_mut().push((id, site.into(), arm)));
+    Override { id }
+}
+
+/// A guard that removes an override made with [`force`] or [`force_default`] when dropped.
+#[must_use = "the override is removed when the guard is dropped"]
+#[derive(Debug)]
+pub struct Override {
+    id: u64,
+}
+
+impl Drop for Override {
+    fn drop(&mut self) {
+        OVERRIDES.with(|overrides| {
+            let mut overrides = overrides.borrow_mut();
+            // Guards can be dropped in any order, so each removes its own override.
+            overrides.retain(|(id, _, _)| *id != self.id);
+        });
+    }
+}
+
+/// Returns the arm forced on `site`, as an index into `arms` or `None` for the default arm, or
+/// `None` if the table is not overridden.
+#[doc(hidden)]
+pub fn forced_arm(site: &str, arms: &[&str]) -> Option<Option<usize>> {
+    OVERRIDES.with(|overrides| {
+        let overrides = overrides.borrow();
+        let (_, _, arm) = overrides
+            .iter()
+            .rev()
+            .find(|(_, forced, _)| forced == site)?;
+        Some(arm.as_ref().map(|arm| {
+            arms.iter()
+                .position(|name| name == arm)
+                .unwrap_or_else(|| panic!("the table `{site}` has no arm named `{arm}`"))
+        }))
+    })
+}
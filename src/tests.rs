@@ -35,4 +35,1629 @@ use super::*;
             _ => false,
         };
         assert_eq!(result, true);
-    }
\ No newline at end of file
+    }
+
+    #[cfg(feature = "testkit")]
+    #[test]
+    fn testkit_reports_unreached_arms() {
+        use crate::testkit::{ArmSearch, Corpus};
+
+        let select = |x: &i32| cond! {
+            *x > 10 => 0,
+            *x > 5 => 1,
+            _ => 2,
+        };
+        let arms = ["big", "medium", "small"];
+        let coverage = ArmSearch::new(&arms).run(Corpus::new([1, 20, 3]), select);
+        assert!(!coverage.is_complete());
+        assert_eq!(coverage.unreached(), ["medium"]);
+        assert_eq!(coverage.witness(0), Some(&20));
+        assert_eq!(coverage.witness(2), Some(&1));
+
+        let coverage = ArmSearch::new(&arms)
+            .seed(7)
+            .run(|rng: &mut crate::testkit::Rng| rng.range_i64(0..20) as i32, select);
+        coverage.assert_complete();
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn checked_cond_matches_declarative_cond() {
+        use crate::checked::cond as checked;
+
+        let classify = |x: i64, flag: bool| checked! {
+            #[cond(exhaustive, disjoint)]
+            x < 0 || (x == 0 && flag) => "low",
+            x == 0 && !flag => "zero",
+            x > 0 && x <= 10 => "mid",
+            x > 10 => "high",
+        };
+        assert_eq!(classify(-3, false), "low");
+        assert_eq!(classify(0, true), "low");
+        assert_eq!(classify(0, false), "zero");
+        assert_eq!(classify(10, false), "mid");
+        assert_eq!(classify(11, true), "high");
+
+        let mut res = String::new();
+        let a = 195;
+        checked! {
+            a < 5 => unreachable!(),
+            test_bool("evaluated", &mut res) => {},
+            test_bool("not evaluated", &mut res) => unreachable!(),
+        }
+        assert_eq!(res, "evaluated");
+        assert_eq!(checked! { a > 200 => 1, _ => 2 }, 2);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn weighted_choice_respects_weights_and_conditions() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            let arm: usize = weighted_choice! { &mut rng;
+                3 => 0,
+                1 => 1,
+                100 if false => 2,
+                _ => 2,
+            };
+            counts[arm] += 1;
+        }
+        assert_eq!(counts[2], 0);
+        assert!(counts[0] > counts[1] * 2, "{counts:?}");
+
+        let none = weighted_choice! { &mut rng; 0 => 1, (-1) => 2, 5 if false => 3, _ => 4 };
+        assert_eq!(none, 4);
+
+        let mut evaluated = false;
+        weighted_choice! { &mut rng;
+            1 if { evaluated = true; false } => unreachable!(),
+        }
+        assert!(evaluated);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn cond_rand_picks_among_true_arms() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let mut seen = [false; 4];
+        for _ in 0..100 {
+            let mut evaluated = 0;
+            let arm = cond_rand! { &mut rng;
+                { evaluated += 1; true } => 0,
+                { evaluated += 1; false } => 1,
+                { evaluated += 1; true } => 2,
+                _ => 3,
+            };
+            seen[arm] = true;
+            assert_eq!(evaluated, 3);
+        }
+        assert_eq!(seen, [true, false, true, false]);
+
+        let none = cond_rand! { &mut rng; false => 1, _ => 2 };
+        assert_eq!(none, 2);
+    }
+
+    #[cfg(feature = "either")]
+    #[test]
+    fn cond_either_nests_arm_types() {
+        use either::Either::{Left, Right};
+
+        let pick = |n: u32| cond_either! {
+            n == 0 => "zero",
+            n < 10 => n,
+            _ => 'x',
+        };
+        assert_eq!(pick(0), Left("zero"));
+        assert_eq!(pick(3), Right(Left(3)));
+        assert_eq!(pick(30), Right(Right('x')));
+        assert_eq!(pick(30).to_string(), "x");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn cond_box_coerces_each_arm() {
+        let items = [1, 2, 3];
+        let iter = |reverse: bool, skip: bool| cond_box! { dyn Iterator<Item = &i32> + '_;
+            reverse => items.iter().rev(),
+            skip => items.iter().skip(1),
+            _ => items.iter(),
+        };
+        assert_eq!(iter(true, true).copied().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_eq!(iter(false, true).copied().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(iter(false, false).copied().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn cond_enum_delegates_traits() {
+        use crate::cond_enum;
+
+        let show = |n: i32| cond_enum! { impl core::fmt::Display + Debug;
+            n < 0 => "negative",
+            n == 0 => 'z',
+            _ => n,
+        };
+        assert_eq!(show(-1).to_string(), "negative");
+        assert_eq!(format!("{:?}", show(0)), "'z'");
+        assert_eq!(show(5).to_string(), "5");
+
+        let items = [1, 2, 3, 4];
+        let iter = |evens: bool| cond_enum! { impl ExactSizeIterator;
+            evens => items.iter().step_by(2),
+            _ => items.iter().skip(1),
+        };
+        assert_eq!(iter(true).len(), 2);
+        assert_eq!(iter(false).len(), 3);
+        assert_eq!(iter(true).collect::<Vec<_>>(), [&1, &3]);
+    }
+
+    #[test]
+    fn cond_expect_panics_with_message() {
+        let classify = |n: i32| cond_expect! { "n must not be zero";
+            n < 0 => "negative",
+            n > 0 => "positive",
+        };
+        assert_eq!(classify(3), "positive");
+
+        let panic = std::panic::catch_unwind(|| classify(0)).unwrap_err();
+        assert_eq!(panic.downcast_ref::<String>().unwrap(), "n must not be zero");
+    }
+
+    #[test]
+    fn cond_unreachable_lists_checked_conditions() {
+        let classify = |n: i32| cond_unreachable! {
+            n < 0 => "negative",
+            n > 0 && n % 2 == 0 => "positive and even",
+        };
+        assert_eq!(classify(-3), "negative");
+
+        let panic = std::panic::catch_unwind(|| classify(3)).unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "no condition held; checked:\n    n < 0\n    n > 0 && n % 2 == 0"
+        );
+    }
+
+    #[test]
+    fn try_cond_reports_location_and_conditions() {
+        let pick = |a: i32| try_cond! {
+            a > 10 => "big",
+            a < -10 => "small",
+        };
+        assert_eq!(pick(11), Ok("big"));
+
+        let line = line!() - 6;
+        let error = pick(0).unwrap_err();
+        assert_eq!(error.file(), file!());
+        assert_eq!(error.line(), line);
+        assert_eq!(error.conditions(), ["a > 10", "a < -10"]);
+        assert!(error.to_string().ends_with("; checked `a > 10`, `a < -10`"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn try_cond_captures_context() {
+        let (user, quota) = ("ada", 3);
+        let result: Result<(), _> = try_cond! { with_context(user, quota * 2);
+            quota > 5 => (),
+        };
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.context(),
+            [("user", "\"ada\"".to_string()), ("quota * 2", "6".to_string())]
+        );
+        assert!(error.to_string().ends_with("; with user = \"ada\", quota * 2 = 6"));
+    }
+
+    #[test]
+    fn cond_with_evaluates_context_once() {
+        let mut calls = 0;
+        let mut next = || {
+            calls += 1;
+            calls * 10
+        };
+        let size = cond_with! { n = next();
+            n > 20 => "large",
+            n > 5 => "medium",
+            _ => "small",
+        };
+        assert_eq!(size, "medium");
+        assert_eq!(calls, 1);
+
+        let name = String::from("ada");
+        let greeting = cond_with! { name;
+            name.is_empty() => "nobody",
+            _ => name.as_str(),
+        };
+        assert_eq!(greeting, "ada");
+        assert_eq!(name, "ada");
+    }
+
+    #[test]
+    fn cond_reduce_takes_first_transition() {
+        let step = |count: Vec<u32>| cond_reduce! { count;
+            count.len() >= 3 => Vec::new(),
+            count.is_empty() => vec![1],
+            count.last() == Some(&2) => { let mut count = count; count.push(3); count },
+        };
+        assert_eq!(step(vec![]), [1]);
+        assert_eq!(step(vec![1, 2, 3]), [] as [u32; 0]);
+        assert_eq!(step(vec![1, 2]), [1, 2, 3]);
+        // No transition applies, so the state is returned unchanged.
+        assert_eq!(step(vec![5]), [5]);
+
+        let mut evaluated = false;
+        let state = cond_reduce! { n = 5;
+            n > 10 => n - 10,
+            { evaluated = true; n < 0 } => 0,
+            _ => n * 2,
+        };
+        assert_eq!(state, 10);
+        assert!(evaluated);
+    }
+
+    #[test]
+    fn score_cond_picks_highest_score() {
+        let pick = |a: i32, b: i32| score_cond! {
+            a => "a",
+            b => "b",
+            5 => "five",
+        };
+        assert_eq!(pick(1, 2), "five");
+        assert_eq!(pick(7, 9), "b");
+        // Ties go to the earliest arm.
+        assert_eq!(pick(9, 9), "a");
+
+        let nan = score_cond! { f32::NAN => 0, 0.5 => 1, 0.1 => 2 };
+        assert_eq!(nan, 1);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn scorer_picks_highest_score() {
+        let threshold = 10;
+        let scorer = crate::Scorer::new()
+            .option(|x: &i32| x - threshold, 'a')
+            .option(|x: &i32| threshold - x, 'b')
+            .option(|_: &i32| 0, 'c');
+        assert_eq!(scorer.len(), 3);
+        assert_eq!(scorer.best(&15), Some(&'a'));
+        assert_eq!(scorer.best(&3), Some(&'b'));
+        assert_eq!(scorer.best_index(&10), Some(0));
+        assert_eq!(crate::Scorer::<i32, ()>::new().best(&1), None);
+    }
+
+    #[test]
+    fn behavior_tree_ticks_in_priority_order() {
+        use crate::behavior::{action, condition, invert, Node, Status};
+
+        let mut tree = selector!(
+            sequence!(
+                condition(|n: &i32| *n > 10),
+                action(|n: &mut i32| {
+                    *n -= 10;
+                    Status::Running
+                }),
+            ),
+            sequence!(
+                invert(condition(|n: &i32| *n == 0)),
+                action(|n: &mut i32| {
+                    *n -= 1;
+                    Status::Success
+                }),
+            ),
+        );
+        let mut n = 12;
+        assert_eq!(tree.tick(&mut n), Status::Running);
+        assert_eq!(n, 2);
+        assert_eq!(tree.tick(&mut n), Status::Success);
+        assert_eq!(tree.tick(&mut n), Status::Success);
+        assert_eq!(n, 0);
+        assert_eq!(tree.tick(&mut n), Status::Failure);
+    }
+
+    #[test]
+    fn hysteresis_and_debounce_suppress_chatter() {
+        let mut hot = hysteresis!(> 10, < 5);
+        let states: Vec<bool> = [9, 11, 9, 6, 5, 4, 9, 10, 11]
+            .into_iter()
+            .map(|t| hot.update(t))
+            .collect();
+        assert_eq!(states, [false, true, true, true, true, false, false, false, true]);
+
+        let mut cold = hysteresis!(< 0, > 3);
+        assert!(cold.update(-1));
+        assert!(cold.update(3));
+        assert!(!cold.update(4));
+        assert!(!cold.is_on());
+
+        let mut flag = crate::gate::Debounce::new(2);
+        let states: Vec<bool> = [true, true, false, true, false, false]
+            .into_iter()
+            .map(|raw| flag.update(raw))
+            .collect();
+        assert_eq!(states, [false, true, true, true, true, false]);
+    }
+
+    #[test]
+    fn flags_are_taken_once() {
+        use crate::flag::{FlagBits, FlagCond};
+
+        let flag = FlagCond::new();
+        assert!(!flag.is_set());
+        flag.set();
+        assert!(flag.is_set());
+        assert!(flag.take());
+        assert!(!flag.take());
+
+        let bits = FlagBits::new();
+        bits.set(0b101);
+        assert!(bits.all(0b101));
+        assert!(!bits.all(0b111));
+        assert!(bits.take(0b001));
+        assert!(!bits.take(0b011));
+        assert_eq!(bits.get(), 0b100);
+        bits.clear(0b100);
+        assert!(!bits.any(u32::MAX));
+    }
+
+    #[test]
+    fn circuit_breaker_opens_and_recovers() {
+        use crate::clock::ManualClock;
+        use crate::gate::CircuitBreaker;
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let secs = Duration::from_secs;
+        let mut breaker = CircuitBreaker::new(&clock, 2, secs(5), secs(10));
+
+        // Failures further apart than the window do not open the circuit.
+        breaker.record_failure();
+        clock.advance(secs(6));
+        breaker.record_failure();
+        assert!(breaker.allow());
+
+        clock.advance(secs(1));
+        assert_eq!(breaker.record(Err::<(), _>("down")), Err("down"));
+        assert!(breaker.is_open());
+        assert!(!breaker.allow());
+
+        clock.advance(secs(10));
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        clock.advance(secs(10));
+        assert!(breaker.allow());
+        assert_eq!(breaker.record(Ok::<_, ()>(1)), Ok(1));
+        assert!(!breaker.is_open());
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn circuit_breaker_retries_an_abandoned_trial() {
+        use crate::clock::ManualClock;
+        use crate::gate::CircuitBreaker;
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let secs = Duration::from_secs;
+        let mut breaker = CircuitBreaker::new(&clock, 1, secs(5), secs(10));
+        breaker.record_failure();
+        clock.advance(secs(10));
+        // The trial is let through, but the rest of the condition fails, so nothing is recorded.
+        let ready = false;
+        assert_eq!(cond! { breaker.allow() && ready => "trial", _ => "skipped" }, "skipped");
+        assert!(!breaker.allow());
+        clock.advance(secs(9));
+        assert!(!breaker.allow());
+        clock.advance(secs(1));
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn circuit_breaker_survives_a_clock_going_backwards() {
+        use crate::clock::ManualClock;
+        use crate::gate::CircuitBreaker;
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let secs = Duration::from_secs;
+        let mut breaker = CircuitBreaker::new(&clock, 2, secs(5), secs(10));
+        clock.advance(secs(100));
+        breaker.record_failure();
+        clock.rewind(secs(50));
+        // The failure seems to be in the future, so the window has not passed.
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        clock.rewind(secs(30));
+        assert!(!breaker.allow());
+        clock.advance(secs(40));
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn rate_limit_uses_a_sliding_window() {
+        use crate::clock::ManualClock;
+        use crate::gate::RateLimit;
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let secs = Duration::from_secs;
+        let mut limit = RateLimit::<_, 2>::new(&clock, secs(10));
+        let mut fire = |at| {
+            clock.set(secs(at));
+            cond! {
+                limit.allow() => "fire",
+                _ => "suppressed",
+            }
+        };
+        assert_eq!(fire(0), "fire");
+        assert_eq!(fire(6), "fire");
+        assert_eq!(fire(9), "suppressed");
+        assert_eq!(fire(10), "fire");
+        assert_eq!(fire(15), "suppressed");
+        assert_eq!(fire(16), "fire");
+        assert_eq!(limit.remaining(), 0);
+        clock.set(secs(30));
+        assert_eq!(limit.remaining(), 2);
+
+        let mut never = RateLimit::<_, 0>::new(&clock, secs(1));
+        assert!(!never.allow());
+
+        // Calls that seem to be in the future after the clock goes back still count.
+        clock.rewind(secs(25));
+        assert_eq!(limit.remaining(), 0);
+        assert!(!limit.allow());
+    }
+
+    #[test]
+    fn flags_come_from_the_provider() {
+        let enabled = ["beta"];
+        let asked = core::cell::Cell::new(0);
+        let provider = |key: &str| {
+            asked.set(asked.get() + 1);
+            key == "dark_mode"
+        };
+        let theme = cond_flags! { flag = provider;
+            flag("beta") && flag("dark_mode") => "beta dark",
+            flag("dark_mode") => "dark",
+            _ => "light",
+        };
+        assert_eq!(theme, "dark");
+        assert_eq!(asked.get(), 2);
+
+        let layout = cond_flags! { enabled = enabled;
+            enabled("beta") => "new",
+            _ => "old",
+        };
+        assert_eq!(layout, "new");
+    }
+
+    #[test]
+    fn target_feature_dispatch_is_cached() {
+        let pick = || cond_target_feature! {
+            "avx512f" => "avx512f",
+            "sve2" => "sve2",
+            "sse2" => "sse2",
+            "neon" => "neon",
+            _ => "scalar",
+        };
+        let first = pick();
+        if cfg!(target_arch = "x86_64") {
+            // SSE2 is part of the x86-64 baseline.
+            assert!(first == "avx512f" || first == "sse2");
+        }
+        if cfg!(target_arch = "aarch64") {
+            // NEON is part of the AArch64 baseline.
+            assert!(first == "sve2" || first == "neon");
+        }
+        assert_eq!(pick(), first);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn specialize_probes_once() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static PROBES: AtomicU32 = AtomicU32::new(0);
+        fn probe() -> bool {
+            PROBES.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+
+        specialize! {
+            fn strategy() -> &'static &'static str {
+                probe() => "fast",
+                _ => "slow",
+            }
+        }
+
+        assert_eq!(*strategy(), "slow");
+        assert_eq!(*strategy(), "slow");
+        assert_eq!(PROBES.load(Ordering::Relaxed), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cond_dbg_evaluates_like_cond() {
+        let mut evaluated = Vec::new();
+        let mut check = |name: &'static str, held: bool| {
+            evaluated.push(name);
+            held
+        };
+        let arm = cond_dbg! {
+            check("a", false) => 0,
+            check("b", true) => 1,
+            check("c", true) => 2,
+            _ => 3,
+        };
+        assert_eq!(arm, 1);
+        assert_eq!(evaluated, ["a", "b"]);
+
+        let level = 7;
+        cond_dbg! { level > 10 => unreachable!() }
+        assert_eq!(cond_dbg! { level > 10 => "high", _ => "low" }, "low");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cond_once_evaluates_once_per_call_site() {
+        let mut probes = 0;
+        let mut pick = |flag: bool| -> &'static u32 {
+            cond_once! { u32;
+                { probes += 1; flag } => 1,
+                _ => 2,
+            }
+        };
+        assert_eq!(pick(false), &2);
+        // The first selection sticks, even though the condition would hold now.
+        assert_eq!(pick(true), &2);
+        assert_eq!(probes, 1);
+
+        let other = cond_once! { u32; true => 3, _ => 4 };
+        assert_eq!(other, &3);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn cond_env_selects_at_compile_time() {
+        use crate::cond_env;
+
+        const NAME: &str = cond_env! {
+            env("COND_TEST_UNSET_VARIABLE") => "unset",
+            env("CARGO_PKG_NAME") == "cond" && env("CARGO_PKG_NAME") != "other" => "cond",
+            _ => "unknown",
+        };
+        assert_eq!(NAME, "cond");
+
+        // Arms that are not selected are not compiled, so their types do not matter.
+        let value = cond_env! {
+            !env("CARGO_PKG_VERSION") => "no version",
+            _ => 1,
+        };
+        assert_eq!(value, 1);
+        let () = cond_env! { false => 1 };
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn cond_version_compares_the_compiler() {
+        use crate::cond_version;
+
+        let modern = cond_version! {
+            < 1.62 || == 1.0.0 => false,
+            >= 1 && (stable || beta || nightly) && !(> 999.0) => true,
+            _ => false,
+        };
+        assert!(modern);
+        // Components compare as numbers, so 1.100 is after 1.62.
+        assert_eq!(cond_version! { != 1.70 && >= 1.62.1 && < 1.100 => 1, _ => 0 }, 1);
+        let () = cond_version! { false => 1 };
+    }
+
+    #[test]
+    fn nested_arms_share_the_default() {
+        let access = |admin: bool, owner: bool, public: bool| cond! {
+            admin =>> {
+                owner => "owner",
+                public =>> {
+                    owner => "unreachable",
+                    _ => "public admin",
+                },
+            },
+            public => "public",
+            _ => "denied",
+        };
+        assert_eq!(access(true, true, false), "owner");
+        assert_eq!(access(true, false, true), "public admin");
+        // A nested table that matches nothing skips straight to the default.
+        assert_eq!(access(true, false, false), "denied");
+        assert_eq!(access(false, false, true), "public");
+
+        let mut log = Vec::new();
+        cond! {
+            log.is_empty() =>> {
+                false => log.push(1),
+            },
+            true => log.push(2),
+        }
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn nested_arms_can_break_and_continue() {
+        let mut seen = Vec::new();
+        for x in [-1, 2, 7, 3] {
+            cond! {
+                x > 0 =>> {
+                    x > 5 => break,
+                    _ => (),
+                },
+                _ => continue,
+            }
+            seen.push(x);
+        }
+        assert_eq!(seen, [2]);
+
+        let mut n = 0;
+        let steps = while_cond! {
+            n < 10 =>> {
+                n % 2 == 0 => n += 3,
+                @unless n > 6 => n += 1,
+            },
+            _ => break n,
+        };
+        assert_eq!(steps, 7);
+    }
+
+    #[test]
+    fn unless_arms_match_false_conditions() {
+        let state = |ready: bool, cancelled: bool| cond! {
+            @unless ready => "wait",
+            cancelled => "stop",
+            _ => "run",
+        };
+        assert_eq!(state(false, true), "wait");
+        assert_eq!(state(true, true), "stop");
+        assert_eq!(state(true, false), "run");
+
+        let nested = |admin: bool, banned: bool| cond! {
+            admin =>> {
+                @unless banned => "admin",
+            },
+            @unless banned => "user",
+            _ => "banned",
+        };
+        assert_eq!(nested(true, false), "admin");
+        assert_eq!(nested(true, true), "banned");
+        assert_eq!(nested(false, false), "user");
+
+        // Tables without nested arms are still plain `if`s, so `break` reaches the loop.
+        let mut count = 0;
+        loop {
+            cond! {
+                @unless count < 3 => break,
+                _ => count += 1,
+            }
+        }
+        assert_eq!(count, 3);
+
+        let failed = cond! { @unless 1 < 2 => &[][..], _ (failed) => failed };
+        // The negation is listed, since that is the condition that was found false.
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].starts_with('!') && failed[0].ends_with("(1 < 2)"));
+    }
+
+    #[test]
+    fn unless_arms_take_any_condition() {
+        let (a, b) = (true, false);
+        let x = &false;
+        assert_eq!(cond! { @unless (a && b) => 1, _ => 0 }, 1);
+        assert_eq!(cond! { @unless *x => 1, _ => 0 }, 1);
+        assert_eq!(cond! { @unless !x => 1, _ => 0 }, 0);
+        assert_eq!(cond! { a && b => 2, @unless (a || b) => 1, @unless !a => 3, _ => 0 }, 3);
+        assert_eq!(cond! { @unless a =>> { @unless *x => 1 }, @unless -1 < 0 => 2, _ => 0 }, 0);
+    }
+
+    #[test]
+    fn nested_tables_bind_their_own_failed_conditions() {
+        let (guest, public, owner) = (true, false, false);
+        let failed = cond! {
+            guest =>> {
+                public => &[][..],
+                owner => &[][..],
+                _ (failed) => failed,
+            },
+            _ => &[][..],
+        };
+        assert_eq!(failed, ["public", "owner"]);
+    }
+
+    #[test]
+    fn long_flat_tables_expand_in_one_step() {
+        // More arms than the default recursion limit would allow one expansion step each.
+        let pick = |n: u32| cond! {
+            n == 0 => 0, n == 1 => 1, n == 2 => 2, n == 3 => 3, n == 4 => 4, n == 5 => 5, n == 6 => 6, n == 7 => 7,
+            n == 8 => 8, n == 9 => 9, n == 10 => 10, n == 11 => 11, n == 12 => 12, n == 13 => 13, n == 14 => 14, n == 15 => 15,
+            n == 16 => 16, n == 17 => 17, n == 18 => 18, n == 19 => 19, n == 20 => 20, n == 21 => 21, n == 22 => 22, n == 23 => 23,
+            n == 24 => 24, n == 25 => 25, n == 26 => 26, n == 27 => 27, n == 28 => 28, n == 29 => 29, n == 30 => 30, n == 31 => 31,
+            n == 32 => 32, n == 33 => 33, n == 34 => 34, n == 35 => 35, n == 36 => 36, n == 37 => 37, n == 38 => 38, n == 39 => 39,
+            n == 40 => 40, n == 41 => 41, n == 42 => 42, n == 43 => 43, n == 44 => 44, n == 45 => 45, n == 46 => 46, n == 47 => 47,
+            n == 48 => 48, n == 49 => 49, n == 50 => 50, n == 51 => 51, n == 52 => 52, n == 53 => 53, n == 54 => 54, n == 55 => 55,
+            n == 56 => 56, n == 57 => 57, n == 58 => 58, n == 59 => 59, n == 60 => 60, n == 61 => 61, n == 62 => 62, n == 63 => 63,
+            n == 64 => 64, n == 65 => 65, n == 66 => 66, n == 67 => 67, n == 68 => 68, n == 69 => 69, n == 70 => 70, n == 71 => 71,
+            n == 72 => 72, n == 73 => 73, n == 74 => 74, n == 75 => 75, n == 76 => 76, n == 77 => 77, n == 78 => 78, n == 79 => 79,
+            n == 80 => 80, n == 81 => 81, n == 82 => 82, n == 83 => 83, n == 84 => 84, n == 85 => 85, n == 86 => 86, n == 87 => 87,
+            n == 88 => 88, n == 89 => 89, n == 90 => 90, n == 91 => 91, n == 92 => 92, n == 93 => 93, n == 94 => 94, n == 95 => 95,
+            n == 96 => 96, n == 97 => 97, n == 98 => 98, n == 99 => 99, n == 100 => 100, n == 101 => 101, n == 102 => 102, n == 103 => 103,
+            n == 104 => 104, n == 105 => 105, n == 106 => 106, n == 107 => 107, n == 108 => 108, n == 109 => 109, n == 110 => 110, n == 111 => 111,
+            n == 112 => 112, n == 113 => 113, n == 114 => 114, n == 115 => 115, n == 116 => 116, n == 117 => 117, n == 118 => 118, n == 119 => 119,
+            n == 120 => 120, n == 121 => 121, n == 122 => 122, n == 123 => 123, n == 124 => 124, n == 125 => 125, n == 126 => 126, n == 127 => 127,
+            n == 128 => 128, n == 129 => 129,
+            _ => u32::MAX,
+        };
+        assert_eq!(pick(129), 129);
+        assert_eq!(pick(130), u32::MAX);
+    }
+
+    #[test]
+    fn conditions_can_start_with_a_variable_named_unless() {
+        let unless = 1;
+        assert_eq!(cond! { unless - 3 > 0 => "positive", _ => "not" }, "not");
+        let unless = true;
+        let x = false;
+        assert_eq!(cond! { unless && x => 1, unless => 2, _ => 0 }, 2);
+    }
+
+    #[test]
+    fn snapshot_evaluates_every_condition_first() {
+        let mut order = Vec::new();
+        let result = cond_snapshot! {
+            { order.push("a"); false } => { order.push("value a"); 1 },
+            { order.push("b"); true } => { order.push("value b"); 2 },
+            { order.push("c"); true } => { order.push("value c"); 3 },
+            _ => 0,
+        };
+        assert_eq!(result, 2);
+        assert_eq!(order, ["a", "b", "c", "value b"]);
+
+        let mut ran = false;
+        cond_snapshot! {
+            false => ran = true,
+        }
+        assert!(!ran);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn audit_log_records_decisions() {
+        use crate::audit::{AuditLog, Entry};
+        use crate::clock::ManualClock;
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let log = AuditLog::new(&clock);
+        let classify = |n: i32| cond_audit! { log, "classify", inputs(n, n * 2);
+            n < 0 => "negative",
+            n == 0 => "zero",
+            _ => "positive",
+        };
+        assert_eq!(classify(0), "zero");
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(classify(5), "positive");
+
+        assert_eq!(log.take(), [
+            Entry {
+                time: Duration::ZERO,
+                site: "classify",
+                arm: Some(1),
+                condition: Some("n == 0"),
+                inputs: vec![("n", "0".into()), ("n * 2", "0".into())],
+            },
+            Entry {
+                time: Duration::from_secs(1),
+                site: "classify",
+                arm: None,
+                condition: None,
+                inputs: vec![("n", "5".into()), ("n * 2", "10".into())],
+            },
+        ]);
+        assert!(log.entries().is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn replay_forces_recorded_decisions() {
+        use crate::audit::{AuditLog, Replay};
+
+        let evaluated = core::cell::Cell::new(0);
+        let check = |sink: &dyn crate::audit::AuditSink, site: &'static str, n: i32| cond_audit! { sink, site;
+            { evaluated.set(evaluated.get() + 1); n > 0 } => "positive",
+            _ => "other",
+        };
+
+        let log: AuditLog = AuditLog::default();
+        check(&log, "a", 1);
+        check(&log, "b", -1);
+        check(&log, "a", -1);
+        assert_eq!(evaluated.get(), 3);
+
+        let replay = Replay::new(log.take());
+        assert_eq!(check(&replay, "a", -5), "positive");
+        assert_eq!(check(&replay, "a", 5), "other");
+        assert_eq!(replay.remaining(), 1);
+        assert_eq!(check(&replay, "b", 5), "other");
+        assert_eq!(evaluated.get(), 3);
+        // Nothing left to replay, so the conditions decide again.
+        assert_eq!(check(&replay, "b", 5), "positive");
+        assert_eq!(evaluated.get(), 4);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn checked_cond_sorts_by_priority() {
+        use crate::checked::cond as checked;
+
+        let mut order = Vec::new();
+        let chosen = checked! {
+            #[cond(domain(x: u8))]
+            #[priority(-5)]
+            { order.push("last"); true } => "last",
+            #[priority(10)]
+            { order.push("first"); false } => "first",
+            #[priority(0)]
+            { order.push("middle"); true } => "middle",
+            _ => "default",
+        };
+        assert_eq!(chosen, "middle");
+        assert_eq!(order, ["first", "middle"]);
+    }
+
+    #[test]
+    fn named_arms_evaluate_like_cond() {
+        let pick = |n: i32| cond_named! { "tests.pick";
+            "negative": n < 0 => -1,
+            "small": n < 10 => 0,
+            _ => 1,
+        };
+        assert_eq!([pick(-4), pick(3), pick(40)], [-1, 0, 1]);
+
+        #[cfg(feature = "registry")]
+        {
+            let site = crate::registry::site("tests.pick").unwrap();
+            assert_eq!(site.arms, ["negative", "small"]);
+            assert_eq!(site.position("small"), Some(1));
+            assert_eq!(site.position("default"), None);
+            assert!(site.file.ends_with("tests.rs"));
+            assert!(crate::registry::sites().any(|site| site.name == "tests.pick"));
+        }
+    }
+
+    #[cfg(feature = "testkit")]
+    #[test]
+    fn testkit_overrides_force_arms() {
+        use crate::testkit::{force, force_default};
+
+        let mut evaluated = 0;
+        let mut pick = |n: i32| cond_named! { "tests.forced";
+            "big": { evaluated += 1; n > 100 } => "big",
+            "small": n > 0 => "small",
+            _ => "other",
+        };
+        assert_eq!(pick(5), "small");
+        {
+            let _big = force("tests.forced", "big");
+            assert_eq!(pick(5), "big");
+            {
+                let _default = force_default("tests.forced");
+                assert_eq!(pick(500), "other");
+            }
+            assert_eq!(pick(-1), "big");
+        }
+        assert_eq!(pick(500), "big");
+        assert_eq!(evaluated, 2);
+
+        // Dropping the outer guard first leaves the inner override in place.
+        let pick = |n: i32| cond_named! { "tests.forced";
+            "big": n > 100 => "big",
+            "small": n > 0 => "small",
+            _ => "other",
+        };
+        let outer = force("tests.forced", "small");
+        let inner = force_default("tests.forced");
+        drop(outer);
+        assert_eq!(pick(5), "other");
+        drop(inner);
+        assert_eq!(pick(500), "big");
+
+        let _missing = force("tests.forced", "missing");
+        let panicked = std::panic::catch_unwind(|| cond_named! { "tests.forced";
+            "big": true => (),
+        });
+        assert!(panicked.is_err());
+    }
+
+    #[cfg(feature = "testkit")]
+    #[test]
+    fn differential_reports_disagreements() {
+        use crate::testkit::{Corpus, Difference, Differential};
+
+        let old = |n: &i32| cond! { *n < 0 => 0, *n < 10 => 1, _ => 2 };
+        let new = |n: &i32| cond! { *n <= 0 => 0, *n < 10 => 1, _ => 2 };
+        let differences = Differential::new().run(Corpus::new([-1, 0, 5, 0, 20]), old, new);
+        assert_eq!(differences, [
+            Difference { input: 0, old: 1, new: 0 },
+            Difference { input: 0, old: 1, new: 0 },
+        ]);
+        assert_eq!(Differential::new().attempts(2).run(Corpus::new(0..), old, new).len(), 1);
+        assert!(Differential::new().run(Corpus::new(1..100), old, new).is_empty());
+    }
+
+    #[cfg(feature = "testkit")]
+    #[test]
+    fn snapshots_diff_changed_decisions() {
+        use crate::testkit::{assert_snapshot, snapshot};
+
+        let old = |n: &i32| cond! { *n < 0 => "negative", *n < 10 => "small", _ => "large" };
+        let new = |n: &i32| cond! { *n <= 0 => "negative", *n < 10 => "small", _ => "large" };
+        let rendered = snapshot([-1, 0, 50], old);
+        assert_eq!(rendered, "-1 => \"negative\"\n0 => \"small\"\n50 => \"large\"\n");
+
+        let path = std::env::temp_dir().join(format!("cond-snapshot-{}.snap", std::process::id()));
+        std::fs::write(&path, &rendered).unwrap();
+        assert_snapshot(&path, &snapshot([-1, 0, 50], old));
+
+        let panic = std::panic::catch_unwind(|| assert_snapshot(&path, &snapshot([-1, 0, 50], new)));
+        let message = panic.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.ends_with("    2 -0 => \"small\"\n    2 +0 => \"negative\"\n"), "{message}");
+
+        std::fs::remove_file(&path).unwrap();
+        let panic = std::panic::catch_unwind(|| assert_snapshot(&path, &rendered));
+        assert!(panic.unwrap_err().downcast::<String>().unwrap().starts_with("there is no snapshot"));
+    }
+
+    #[cfg(feature = "testkit")]
+    fn triage(level: u8) -> &'static str {
+        cond! {
+            level >= 8 => "page",
+            level >= 4 => "ticket",
+            _ => "ignore",
+        }
+    }
+
+    #[cfg(feature = "testkit")]
+    cond_cases! { triage;
+        cond_cases_critical_levels_page: 9 => "page",
+        cond_cases_warnings_open_tickets: 4 => "ticket",
+        cond_cases_noise_is_ignored: 1 + 1 => "ignore",
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_strategies_target_one_arm() {
+        use ::proptest::strategy::{Strategy, ValueTree};
+        use ::proptest::test_runner::TestRunner;
+
+        let select = |n: &i32| cond! { *n < 0 => 0, *n % 2 == 0 => 1, _ => 2 };
+        let mut runner = TestRunner::deterministic();
+        let odd = crate::proptest::arm(-100..100, select, 2);
+        for _ in 0..50 {
+            let n = odd.new_tree(&mut runner).unwrap().current();
+            assert!(n > 0 && n % 2 == 1, "{n}");
+        }
+    }
+
+    #[test]
+    fn cond_vec_matches_scalar_evaluation() {
+        let input: Vec<i32> = (-20..23).collect();
+        let scalar: Vec<i32> = input.iter().map(|&x| cond! { x < -5 => -1, x > 5 => 1, _ => 0 }).collect();
+
+        let mut plain = vec![9; input.len()];
+        cond_vec! { x in input => plain;
+            x < -5 => -1,
+            x > 5 => 1,
+            _ => 0,
+        }
+        assert_eq!(plain, scalar);
+
+        let mut chunked = vec![9; input.len()];
+        cond_vec! { x in &input => chunked, chunks 8;
+            x < -5 => -1,
+            x > 5 => 1,
+            _ => 0,
+        }
+        assert_eq!(chunked, scalar);
+
+        let pairs = [(1, 2), (4, 3)];
+        let mut larger = [0; 2];
+        cond_vec! { (a, b) in pairs => larger; a > b => a, _ => b }
+        assert_eq!(larger, [2, 4]);
+    }
+
+    #[test]
+    #[should_panic = "lengths differ"]
+    fn cond_vec_checks_lengths() {
+        let mut output = [0; 2];
+        cond_vec! { x in [1, 2, 3] => output; _ => x }
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn cond_enum_unifies_futures() {
+        use crate::cond_enum;
+        use core::future::Future;
+        use core::task::{Context, Poll, Waker};
+
+        struct Pending(u32, u32);
+        impl Future for Pending {
+            type Output = u32;
+            fn poll(mut self: core::pin::Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u32> {
+                if self.0 == 0 {
+                    return Poll::Ready(self.1);
+                }
+                self.0 -= 1;
+                Poll::Pending
+            }
+        }
+
+        let pick = |slow: bool| cond_enum! { impl Future;
+            slow => Pending(2, 20),
+            _ => async { 10 },
+        };
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut slow = core::pin::pin!(pick(true));
+        assert_eq!(slow.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(slow.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(slow.as_mut().poll(&mut cx), Poll::Ready(20));
+        let mut fast = core::pin::pin!(pick(false));
+        assert_eq!(fast.as_mut().poll(&mut cx), Poll::Ready(10));
+    }
+
+    #[test]
+    fn cond_or_default_falls_back_to_default() {
+        let pick = |n: i32| -> Vec<i32> {
+            cond_or_default! {
+                n > 10 => vec![1],
+                n > 5 => vec![2, 2],
+            }
+        };
+        assert_eq!(pick(20), [1]);
+        assert_eq!(pick(7), [2, 2]);
+        assert!(pick(0).is_empty());
+    }
+
+    #[test]
+    fn default_arm_binds_failed_conditions() {
+        let explain = |n: i32| cond! {
+            n < 0 => String::from("negative"),
+            n % 2 == 0 && n > 100 => String::from("big even"),
+            _ (failed) => failed.join(" | "),
+        };
+        assert_eq!(explain(-1), "negative");
+        assert_eq!(explain(3), "n < 0 | n % 2 == 0 && n > 100");
+
+        let only_default = cond! { _ (failed) => failed.len() };
+        assert_eq!(only_default, 0);
+    }
+
+    #[test]
+    fn position_cond_finds_the_first_match() {
+        let readings = [3, 7, -2, 40, 9];
+        let first = position_cond! { &x in &readings;
+            x > 30 => "spike",
+            x < 0 => "negative",
+        };
+        assert_eq!(first, Some((2, "negative")));
+
+        let pairs = vec![(1, 1), (2, 3), (5, 4)];
+        assert_eq!(position_cond! { (a, b) in pairs.clone(); a > b => a - b }, Some((2, 1)));
+        assert_eq!(position_cond! { (a, _) in pairs; a > 10 => () }, None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn range_cond_looks_up_and_validates() {
+        use crate::{RangeCond, RangeError};
+
+        let speed = range_cond! { 0..30 => "slow", 30..80 => "cruise", 120.. => "fast" };
+        assert_eq!(speed.get(&0), Some(&"slow"));
+        assert_eq!(speed.get(&79), Some(&"cruise"));
+        assert_eq!(speed.get(&100), None);
+        assert_eq!(speed.position(&500), Some(2));
+
+        assert_eq!(
+            RangeCond::from_ranges([(0..10, 'a'), (5..20, 'b')]),
+            Err(RangeError::Overlap(5, Some(10)))
+        );
+        assert_eq!(RangeCond::from_ranges([(3..3, 'a')]), Err(RangeError::Empty(3..3)));
+        assert_eq!(
+            RangeCond::contiguous([(10..20, 'b'), (0..8, 'a')]),
+            Err(RangeError::Gap(8..10))
+        );
+        assert_eq!(
+            RangeCond::from_thresholds([(1, 'a'), (1, 'b')]),
+            Err(RangeError::Overlap(1, None))
+        );
+        let error = RangeCond::<i32, char>::from_ranges([(0..10, 'a'), (5..20, 'b')]).unwrap_err();
+        assert_eq!(error.to_string(), "more than one range covers 5..10");
+    }
+
+    #[test]
+    fn schedule_windows_follow_days_and_offsets() {
+        use crate::clock::ManualClock;
+        use crate::schedule::{Days, Schedule};
+        use core::time::Duration;
+
+        const HOUR: u64 = 3600;
+        // Monday 5 January 1970, 00:00 UTC.
+        const MONDAY: u64 = 4 * 24 * HOUR;
+        let clock = ManualClock::new();
+        let night_shift = Schedule::new(&clock)
+            .days(Days::FRIDAY | Days::SATURDAY)
+            .between((22, 0), (6, 0));
+        let at = |hours: u64| {
+            clock.set(Duration::from_secs(MONDAY + hours * HOUR));
+            night_shift.is_active()
+        };
+        assert!(!at(4 * 24 + 21));
+        assert!(at(4 * 24 + 23));
+        assert!(at(5 * 24 + 5));
+        assert!(at(6 * 24 + 5));
+        assert!(!at(6 * 24 + 6));
+        assert!(!at(6 * 24 + 23));
+
+        let clock = ManualClock::new();
+        clock.set(Duration::from_secs(MONDAY + 20 * HOUR));
+        let office = Schedule::new(&clock).days(Days::MONDAY).between((9, 0), (17, 0));
+        assert!(!office.is_active());
+        assert!(office.clone().utc_offset_minutes(-5 * 60).is_active());
+        assert!(Schedule::new(&clock).is_active());
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn cond_sql_matches_its_sql() {
+        crate::cond_sql! {
+            fn risk(score: i32, #[column = "a.flagged"] flagged: bool, r#type: char) -> i32 {
+                flagged || score < -10 => 3,
+                !(flagged) && r#type != 'x' && (score <= 0 || score > 900) => 2,
+                _ => score,
+            }
+        }
+        assert_eq!(risk(-20, false, 'a'), 3);
+        assert_eq!(risk(0, false, 'a'), 2);
+        assert_eq!(risk(0, false, 'x'), 0);
+        assert_eq!(
+            RISK_SQL,
+            "CASE WHEN a.flagged OR score < -10 THEN 3 \
+             WHEN NOT (a.flagged) AND type <> 'x' AND (score <= 0 OR score > 900) THEN 2 ELSE score END"
+        );
+    }
+
+    #[test]
+    fn cooldowns_skip_arms_until_they_pass() {
+        use crate::clock::ManualClock;
+        use crate::gate::{Cooldown, TickCooldown};
+        use core::time::Duration;
+
+        let clock = ManualClock::new();
+        let mut heal = Cooldown::new(&clock, Duration::from_secs(10));
+        assert!(heal.allow());
+        assert!(!heal.allow());
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(heal.remaining(), Duration::from_secs(6));
+        clock.advance(Duration::from_secs(6));
+        assert!(heal.is_ready());
+        assert!(heal.allow());
+        heal.reset();
+        assert!(heal.allow());
+        clock.rewind(Duration::from_secs(4));
+        assert_eq!(heal.remaining(), Duration::from_secs(10));
+
+        let mut shield = TickCooldown::new(2);
+        let mut blink = TickCooldown::new(0);
+        let mut log = Vec::new();
+        for _ in 0..4 {
+            log.push(cond! {
+                shield.allow() => "shield",
+                blink.allow() => "blink",
+                _ => "wait",
+            });
+            shield.tick();
+        }
+        assert_eq!(log, ["shield", "blink", "shield", "blink"]);
+    }
+
+    #[test]
+    fn while_cond_runs_until_a_break_or_no_match() {
+        let mut n = 27_u64;
+        let mut steps = 0;
+        while_cond! {
+            n == 1 => break,
+            n % 2 == 0 => n /= 2,
+            n % 2 != 0 => n = 3 * n + 1,
+        }
+        // Counts the steps separately, ending when nothing matches.
+        let mut m = 27_u64;
+        while_cond! {
+            m != 1 => {
+                m = if m % 2 == 0 { m / 2 } else { 3 * m + 1 };
+                steps += 1;
+            },
+        }
+        assert_eq!((n, m, steps), (1, 1, 111));
+
+        let mut tries = 0;
+        let found = while_cond! {
+            tries > 5 => break None,
+            tries * tries == 16 => break Some(tries),
+            _ => tries += 1,
+        };
+        assert_eq!(found, Some(4));
+    }
+
+    fn is_short(word: &str) -> bool {
+        word.len() < 4
+    }
+
+    cond_static! {
+        static WORDS: crate::StaticCond<str, usize> = {
+            is_short => |_| 0,
+            |word| word.starts_with('x') => str::len,
+        };
+    }
+
+    #[test]
+    fn cond_static_walks_its_arms() {
+        assert_eq!(WORDS.eval("abc"), Some(0));
+        assert_eq!(WORDS.eval("xylophone"), Some(9));
+        assert_eq!(WORDS.eval("banana"), None);
+        assert_eq!(WORDS.position("xylophone"), Some(1));
+        assert!(!WORDS.has_default());
+        let names: Vec<_> = WORDS.arms().iter().map(|arm| arm.name).collect();
+        assert_eq!(names, ["is_short", "|word| word.starts_with('x')"]);
+        std::thread::spawn(|| assert_eq!(WORDS.eval("xyz"), Some(0))).join().unwrap();
+    }
+
+    #[test]
+    fn parse_cond_dispatches_on_probes() {
+        let mode = "strict";
+        let parse = |text: &str| parse_cond! { text;
+            starts_with('{') && ends_with('}') => |t: &str| ("object", t.len()),
+            !(starts_with("--") || starts_with('#')) && mode.starts_with("lax") => |t: &str| ("lax", t.len()),
+            |t: &str| t.trim().parse::<i64>().is_ok() => |t: &str| ("number", t.trim().len()),
+            str::is_empty => |_| ("empty", 0),
+        };
+        assert_eq!(parse("{}"), Ok(("object", 2)));
+        assert_eq!(parse(" 42 "), Ok(("number", 2)));
+        assert_eq!(parse(""), Ok(("empty", 0)));
+
+        let error = parse("-- comment").unwrap_err();
+        assert_eq!(error.conditions().len(), 4);
+        assert_eq!(error.conditions()[3], "str::is_empty");
+
+        let owned = String::from("abc");
+        assert_eq!(parse_cond! { owned; ends_with("bc") => str::len }, Ok(3));
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn derive_classify_orders_fields_and_arms() {
+        #[derive(crate::Classify)]
+        #[classify(output = &'static str)]
+        struct Reading<T: Copy + PartialOrd> {
+            #[classify(level > self.limit => "over")]
+            level: T,
+            limit: T,
+            #[classify(stale => "stale", level == self.limit => "at limit")]
+            stale: bool,
+        }
+
+        let reading = |level, stale| Reading { level, limit: 5, stale };
+        assert_eq!(reading(6, true).classify(), Some("over"));
+        assert_eq!(reading(5, true).classify(), Some("stale"));
+        assert_eq!(reading(5, false).classify(), Some("at limit"));
+        assert_eq!(reading(1, false).classify(), None);
+    }
+
+    #[test]
+    fn const_cond_instantiates_only_the_chosen_arm() {
+        use crate::dispatch::{Bool, Dispatch, Select};
+
+        trait Kernel {
+            const NAME: &'static str;
+        }
+        struct Simd;
+        struct Scalar;
+        impl Kernel for Simd {
+            const NAME: &'static str = "simd";
+        }
+        impl Kernel for Scalar {
+            const NAME: &'static str = "scalar";
+        }
+
+        fn pick<const FAST: bool, const DEBUG: bool>() -> String
+        where
+            Bool<FAST>: Dispatch,
+            Bool<DEBUG>: Dispatch,
+            Select<FAST, Simd, Scalar>: Kernel,
+        {
+            let name = <Select<FAST, Simd, Scalar> as Kernel>::NAME;
+            const_cond! {
+                DEBUG => format!("{name} (debug)"),
+                // Would fail to compile if it were instantiated.
+                false => { const { panic!("instantiated") } },
+                _ => name.to_string(),
+            }
+        }
+        assert_eq!(pick::<true, false>(), "simd");
+        assert_eq!(pick::<false, true>(), "scalar (debug)");
+
+        let mut log = Vec::new();
+        const_cond! { true => log.push(1) }
+        const_cond! { <Bool<false> as Dispatch>::VALUE => log.push(2) }
+        assert_eq!(log, [1]);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn pure_tables_accept_side_effect_free_conditions() {
+        let jobs = [3, 1, 4];
+        let picked = crate::checked::cond! {
+            #[cond(pure)]
+            jobs.is_empty() => None,
+            jobs.iter().any(|&job| job > 3) => jobs.iter().max().copied(),
+            _ => jobs.first().copied(),
+        };
+        assert_eq!(picked, Some(4));
+    }
+
+    #[test]
+    fn cond_snapshot_runs_every_probe() {
+        let mut probed = Vec::new();
+        let mut probe = |name: &'static str, up: bool| {
+            probed.push(name);
+            up
+        };
+        let route = cond_snapshot! {
+            probe("primary", true) => "primary",
+            probe("replica", true) => "replica",
+            probe("cache", false) => "cache",
+            _ => "none",
+        };
+        assert_eq!(route, "primary");
+        assert_eq!(probed, ["primary", "replica", "cache"]);
+    }
+
+    #[test]
+    fn cond_strict_runs_every_probe() {
+        let mut probed = Vec::new();
+        let mut probe = |name: &'static str, up: bool| {
+            probed.push(name);
+            up
+        };
+        let route = cond_strict! {
+            probe("primary", false) => "primary",
+            probe("replica", true) => "replica",
+            probe("cache", true) => "cache",
+            _ => "none",
+        };
+        assert_eq!(route, "replica");
+        assert_eq!(probed, ["primary", "replica", "cache"]);
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn cond_named_evaluations_become_spans() {
+        use opentelemetry::{KeyValue, Value};
+        use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        opentelemetry::global::set_tracer_provider(provider);
+
+        for load in [10, 95] {
+            cond_named! { "tests.otel";
+                "overloaded": load > 90 => (),
+                "busy": load > 50 => (),
+                _ => (),
+            }
+        }
+
+        let spans: Vec<_> = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .filter(|span| span.name == "tests.otel")
+            .collect();
+        let attribute = |attributes: &[KeyValue], key: &str| {
+            attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.clone())
+        };
+        assert_eq!(spans.len(), 2);
+        assert_eq!(attribute(&spans[0].attributes, "cond.arm"), Some(Value::from("_")));
+        assert_eq!(attribute(&spans[0].attributes, "cond.conditions_evaluated"), Some(Value::I64(2)));
+        assert_eq!(attribute(&spans[1].attributes, "cond.arm"), Some(Value::from("overloaded")));
+        assert_eq!(attribute(&spans[1].attributes, "cond.conditions_evaluated"), Some(Value::I64(1)));
+        assert_eq!(attribute(&spans[1].attributes, "cond.site"), Some(Value::from("tests.otel")));
+        assert!(attribute(&spans[1].attributes, "cond.evaluation_ns").is_some());
+
+        crate::otel::SAMPLER.set("tests.otel.sampled", crate::sample::Rate::ALL.non_default_only());
+        for load in [10, 95, 20] {
+            cond_named! { "tests.otel.sampled"; "overloaded": load > 90 => (), _ => () }
+        }
+        let sampled = exporter.get_finished_spans().unwrap();
+        let sampled: Vec<_> = sampled.iter().filter(|span| span.name == "tests.otel.sampled").collect();
+        assert_eq!(sampled.len(), 1);
+        assert_eq!(attribute(&sampled[0].attributes, "cond.arm"), Some(Value::from("overloaded")));
+    }
+
+    #[test]
+    fn site_sampler_counts_without_allocating() {
+        use crate::sample::{Rate, SiteSampler};
+
+        let sampler = SiteSampler::new(Rate::one_in(2));
+        let sampled: Vec<_> = (0..5).map(|_| sampler.sample(Some(0))).collect();
+        assert_eq!(sampled, [true, false, true, false, true]);
+
+        sampler.set(Rate::ALL.non_default_only());
+        assert_eq!(sampler.rate(), Rate::ALL.non_default_only());
+        assert!(!sampler.sample(None));
+        assert!(sampler.sample(Some(1)));
+        sampler.set(Rate::NONE);
+        assert!(!sampler.sample(Some(1)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sampled_sink_records_a_sample() {
+        use crate::audit::{AuditLog, Sampled};
+        use crate::clock::InstantClock;
+        use crate::sample::Rate;
+
+        let sink = Sampled::new(AuditLog::<InstantClock>::default());
+        sink.sampler().set_default(Rate::one_in(3));
+        sink.sampler().set("tests.rare", Rate::NONE);
+        for n in 0..7 {
+            cond_audit! { sink, "tests.sampled", inputs(n); n > 100 => (), _ => () }
+            cond_audit! { sink, "tests.rare"; n > 100 => () }
+        }
+        let inputs: Vec<_> = sink.inner().take().into_iter().map(|entry| entry.inputs[0].1.clone()).collect();
+        assert_eq!(inputs, ["0", "3", "6"]);
+
+        // Changing a rate restarts its count.
+        sink.sampler().set("tests.sampled", Rate::one_in(2).non_default_only());
+        assert_eq!(sink.sampler().rate("tests.rare"), Rate::NONE);
+        for n in [1, 200, 2, 300, 400] {
+            cond_audit! { sink, "tests.sampled", inputs(n); n > 100 => (), _ => () }
+        }
+        let inputs: Vec<_> = sink.inner().take().into_iter().map(|entry| entry.inputs[0].1.clone()).collect();
+        assert_eq!(inputs, ["200", "400"]);
+        assert!(sink.sampler().unset("tests.sampled"));
+        assert_eq!(sink.sampler().rate("tests.sampled"), Rate::one_in(3));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn cond_named_logs_with_defmt() {
+        // The messages are filtered out unless `DEFMT_LOG` enables them, but the names are still
+        // interned and the table still selects its arms.
+        let pick = |load: u32| cond_named! { "tests.defmt";
+            "overloaded": load > 90 => 2,
+            "busy": load > 50 => 1,
+            _ => 0,
+        };
+        assert_eq!([pick(95), pick(60), pick(10)], [2, 1, 0]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn arm_cache_evicts_the_least_recently_used_input() {
+        use crate::cache::ArmCache;
+        use std::cell::Cell;
+
+        let evaluations = Cell::new(0);
+        let select = |&n: &i32| {
+            evaluations.set(evaluations.get() + 1);
+            (n > 0).then_some(0)
+        };
+        let mut cache = ArmCache::new(2);
+        assert_eq!(cache.arm(1, select), Some(0));
+        assert_eq!(cache.arm(-1, select), None);
+        assert_eq!(cache.arm(1, select), Some(0));
+        // Evicts -1, which was used least recently.
+        assert_eq!(cache.arm(2, select), Some(0));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(evaluations.get(), 3);
+        cache.arm(1, select);
+        assert_eq!(evaluations.get(), 3);
+        cache.arm(-1, select);
+        assert_eq!(evaluations.get(), 4);
+
+        assert!(cache.invalidate(&-1));
+        assert!(!cache.invalidate(&-1));
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(ArmCache::new(0).arm(5, select), Some(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn arm_cache_reuses_the_slots_of_invalidated_inputs() {
+        use crate::cache::ArmCache;
+
+        let mut cache = ArmCache::new(3);
+        for n in 0..3 {
+            cache.arm(n, |_| None);
+        }
+        assert!(cache.invalidate(&1));
+        cache.arm(0, |_| None);
+        cache.arm(3, |_| None);
+        // 2 is now the least recently used input, then 0, then 3.
+        cache.arm(4, |_| None);
+        assert_eq!(cache.len(), 3);
+        let misses = cache.misses();
+        for n in [0, 3, 4] {
+            cache.arm(n, |_| None);
+        }
+        assert_eq!(cache.misses(), misses);
+        cache.arm(2, |_| None);
+        cache.arm(0, |_| None);
+        assert_eq!(cache.misses(), misses + 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn static_tables_do_not_share_cached_arms() {
+        use crate::cache::ArmCache;
+        use crate::StaticCond;
+
+        cond_static! {
+            static SIGN: StaticCond<i32, &'static str> = {
+                |&n| n < 0 => |_| "negative",
+                |&n| n > 0 => |_| "positive",
+                _ => |_| "zero",
+            };
+        }
+        cond_static! {
+            static SMALL: StaticCond<i32, &'static str> = {
+                |&n| n < 10 => |_| "small",
+            };
+        }
+        let mut cache = ArmCache::new(4);
+        assert_eq!(SIGN.eval_cached(&5, &mut cache), Some("positive"));
+        assert_eq!(SMALL.eval_cached(&5, &mut cache), Some("small"));
+        assert_eq!(SIGN.eval_cached(&5, &mut cache), Some("positive"));
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn extract_binds_the_first_success() {
+        let mut tried = Vec::new();
+        let mut attempt = |name: &'static str, result: Result<i32, &'static str>| {
+            tried.push(name);
+            result
+        };
+        let value = extract! {
+            n = attempt("first", Err("bad")) => n,
+            n = attempt("second", Ok(2)) => n * 10,
+            n = attempt("third", Ok(3)) => n * 100,
+            _ => 0,
+        };
+        assert_eq!(value, 20);
+        assert_eq!(tried, ["first", "second"]);
+
+        let mut seen = None;
+        extract! { Some(x) = Some([1, 2].first()) => seen = Some(*x) }
+        assert_eq!(seen, Some(1));
+    }
@@ -0,0 +1,80 @@
+//! Runtime feature flags, resolved against a provider injected into a table.
+//!
+//! [`FlagProvider`] is the interface to whatever decides which flags are on: a client for a flag
+//! service, a configuration file, or a fixed set in tests. [`cond_flags!`](crate::cond_flags)
+//! makes a provider available to a table's conditions through a function of your choosing.
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, string::String};
+
+/// A source of runtime feature flags.
+pub trait FlagProvider {
+    /// Returns whether the flag named `key` is enabled.
+    fn enabled(&self, key: &str) -> bool;
+}
+
+impl<F: Fn(&str) -> bool> FlagProvider for F {
+    fn enabled(&self, key: &str) -> bool {
+        self(key)
+    }
+}
+
+/// The flags listed are enabled, and every other flag is disabled.
+impl FlagProvider for [&str] {
+    fn enabled(&self, key: &str) -> bool {
+        self.contains(&key)
+    }
+}
+
+/// The flags listed are enabled, and every other flag is disabled.
+impl<const N: usize> FlagProvider for [&str; N] {
+    fn enabled(&self, key: &str) -> bool {
+        self.contains(&key)
+    }
+}
+
+/// The flags in the set are enabled, and every other flag is disabled.
+#[cfg(feature = "alloc")]
+impl FlagProvider for BTreeSet<String> {
+    fn enabled(&self, key: &str) -> bool {
+        self.contains(key)
+    }
+}
+
+/// The flags in the set are enabled, and every other flag is disabled.
+#[cfg(feature = "std")]
+impl<S: core::hash::BuildHasher> FlagProvider for std::collections::HashSet<String, S> {
+    fn enabled(&self, key: &str) -> bool {
+        self.contains(key)
+    }
+}
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but with a function that looks up feature flags in a
+/// [`FlagProvider`](crate::toggle::FlagProvider).
+///
+/// The first argument is `name = provider`, followed by a semicolon. The provider is evaluated
+/// once and borrowed, and `name(key)` can then be used in every condition and value to ask it
+/// whether the flag `key` is enabled.
+///
+/// ```
+/// # use cond::cond_flags;
+/// #[derive(PartialEq)]
+/// enum Region { Eu, Us }
+///
+/// let provider = ["new_pricing"];
+/// let price = |region: Region| cond_flags! { flag = provider;
+///     flag("new_pricing") && region == Region::Eu => 90,
+///     flag("holiday_sale") => 80,
+///     _ => 100,
+/// };
+/// assert_eq!(price(Region::Eu), 90);
+/// assert_eq!(price(Region::Us), 100);
+/// ```
+macro_rules! cond_flags {
+    ($name:ident = $provider:expr; $($arms:tt)*) => {{
+        let provider = &$provider;
+        let $name = |key: &str| $crate::toggle::FlagProvider::enabled(provider, key);
+        $crate::cond! { $($arms)* }
+    }};
+}
@@ -0,0 +1,124 @@
+//! Variants of [`cond!`](crate::cond) for arms whose values have different types.
+
+#[cfg(feature = "either")]
+#[macro_export]
+/// Like [`cond!`](crate::cond), but wraps the arm values in nested [`Either`]s so that each arm can
+/// have a different type.
+///
+/// The first arm's value becomes `Left(value)`, the second's `Right(Left(value))`, and so on, with
+/// the default arm innermost on the right. `Either` implements traits such as [`Iterator`] and
+/// [`Display`](core::fmt::Display) when both sides do, so the result can be used through those
+/// traits without boxing. A default arm is required.
+///
+/// [`Either`]: either::Either
+///
+/// ```
+/// # use cond::cond_either;
+/// fn evens_or_odds(items: &[u32], evens: bool, reverse: bool) -> impl Iterator<Item = &u32> {
+///     let parity = if evens { 0 } else { 1 };
+///     cond_either! {
+///         reverse => items.iter().rev().filter(move |x| *x % 2 == parity),
+///         _ => items.iter().filter(move |x| *x % 2 == parity),
+///     }
+/// }
+///
+/// let items = [1, 2, 3, 4];
+/// assert_eq!(evens_or_odds(&items, true, true).collect::<Vec<_>>(), [&4, &2]);
+/// assert_eq!(evens_or_odds(&items, false, false).collect::<Vec<_>>(), [&1, &3]);
+/// ```
+macro_rules! cond_either {
+    (_ => $default:expr $(,)?) => {
+        $default
+    };
+    ($condition:expr => $value:expr, $($rest:tt)+) => {
+        if $condition {
+            $crate::__private::Either::Left($value)
+        } else {
+            $crate::__private::Either::Right($crate::cond_either!($($rest)+))
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+#[macro_export]
+/// Like [`cond!`](crate::cond), but boxes each arm value into a `Box<dyn Trait>`, so that arms can
+/// return different concrete types.
+///
+/// The first argument is the trait object type, followed by a semicolon. A default arm is
+/// required.
+///
+/// ```
+/// # use cond::cond_box;
+/// use std::fmt::Display;
+///
+/// fn label(code: u32) -> Box<dyn Display> {
+///     cond_box! { dyn Display;
+///         code == 0 => "ok",
+///         code < 100 => code,
+///         _ => format!("unknown ({code})"),
+///     }
+/// }
+/// assert_eq!(label(0).to_string(), "ok");
+/// assert_eq!(label(42).to_string(), "42");
+/// assert_eq!(label(500).to_string(), "unknown (500)");
+/// ```
+macro_rules! cond_box {
+    ($trait:ty; $($condition:expr => $value:expr,)* _ => $default:expr $(,)?) => {
+        $(if $condition {
+            $crate::__private::Box::new($value) as $crate::__private::Box<$trait>
+        } else)* {
+            $crate::__private::Box::new($default) as $crate::__private::Box<$trait>
+        }
+    };
+}
+
+/// Like [`cond!`](crate::cond), but wraps the arm values in a generated enum that delegates the
+/// listed traits, so that arms can return different types without allocating.
+///
+/// The first argument is `impl` followed by the traits to delegate, separated by `+`, then a
+/// semicolon. The enum has one variant per arm, each generic over that arm's value type, and
+/// implements each listed trait when all of the value types do. The supported traits are
+/// [`Iterator`] (all arms must yield the same item type), [`DoubleEndedIterator`],
+/// [`ExactSizeIterator`], [`Display`](core::fmt::Display), [`Debug`](core::fmt::Debug) and
+/// [`Future`] (all arms must have the same output type). The enum cannot be named, so return it as
+/// `impl Trait`. A default arm is required.
+///
+/// This is the static-dispatch counterpart of [`cond_box!`](crate::cond_box). For futures, it
+/// replaces `Box::pin`: the selected arm's future is polled in place. The `Future` implementation
+/// contains an `unsafe` block for pin projection, so it cannot be used in crates that forbid
+/// `unsafe_code`.
+///
+/// ```
+/// # use cond::cond_enum;
+/// fn digits(n: u32, reverse: bool, skip_zeros: bool) -> impl DoubleEndedIterator<Item = u32> {
+///     let digits = (0..10).map(move |i| n / 10u32.pow(i) % 10).take(n.to_string().len());
+///     cond_enum! { impl DoubleEndedIterator;
+///         reverse => digits.rev(),
+///         skip_zeros => digits.filter(|&d| d != 0),
+///         _ => digits,
+///     }
+/// }
+///
+/// assert_eq!(digits(1203, true, false).collect::<Vec<_>>(), [1, 2, 0, 3]);
+/// assert_eq!(digits(1203, false, true).collect::<Vec<_>>(), [3, 2, 1]);
+/// assert_eq!(digits(1203, false, false).next_back(), Some(1));
+/// ```
+///
+/// ```
+/// # use cond::cond_enum;
+/// async fn cached(key: u32) -> String { format!("cached {key}") }
+/// async fn fetch(key: u32) -> String { format!("fetched {key}") }
+///
+/// fn lookup(key: u32, hit: bool) -> impl std::future::Future<Output = String> {
+///     cond_enum! { impl Future;
+///         hit => cached(key),
+///         _ => fetch(key),
+///     }
+/// }
+/// # use std::future::Future;
+/// # let mut future = std::pin::pin!(lookup(7, true));
+/// # let poll = future.as_mut().poll(&mut std::task::Context::from_waker(std::task::Waker::noop()));
+/// # assert_eq!(poll, std::task::Poll::Ready("cached 7".to_string()));
+/// ```
+#[cfg(feature = "macros")]
+pub use cond_macros::cond_enum;
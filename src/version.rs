@@ -0,0 +1,24 @@
+//! Arms selected by the version of the compiler.
+
+/// Like [`cond!`](crate::cond), but with conditions on the version of the compiler, evaluated at
+/// compile time, so only the selected arm's value is compiled.
+///
+/// A condition can compare the version with `<`, `<=`, `==`, `!=`, `>=` or `>`, as in `>= 1.75`
+/// or `< 1.80.1`, test the release channel with `stable`, `beta` or `nightly`, and combine those
+/// with `!`, `&&`, `||` and parentheses. If no condition holds and there is no default arm, the
+/// invocation expands to `()`.
+///
+/// Because the other arms are not compiled, they can use language features or library items the
+/// compiler does not have:
+///
+/// ```
+/// # use cond::cond_version;
+/// fn is_sorted(values: &[i32]) -> bool {
+///     cond_version! {
+///         >= 1.82 => values.is_sorted(),
+///         _ => values.windows(2).all(|pair| pair[0] <= pair[1]),
+///     }
+/// }
+/// assert!(is_sorted(&[1, 2, 2, 5]));
+/// ```
+pub use cond_macros::cond_version;
@@ -0,0 +1,33 @@
+//! A variant of [`cond!`](crate::cond) that shares one context value between all arms.
+
+#[macro_export]
+/// Like [`cond!`](crate::cond), but first evaluates a context expression exactly once and binds it
+/// to a name usable in every condition and value.
+///
+/// The first argument is `name = expression`, followed by a semicolon. `cond_with!(name; ...)` is
+/// shorthand for `cond_with!(name = &name; ...)`.
+///
+/// ```
+/// # use cond::cond_with;
+/// struct Connection { ready: bool, retries: u32 }
+///
+/// let mut opened = 0;
+/// let mut open = || { opened += 1; Connection { ready: false, retries: 1 } };
+///
+/// let action = cond_with! { conn = open();
+///     conn.ready => "send",
+///     conn.retries < 3 => "retry",
+///     _ => "give up",
+/// };
+/// assert_eq!(action, "retry");
+/// assert_eq!(opened, 1);
+/// ```
+macro_rules! cond_with {
+    ($name:ident = $context:expr; $($arms:tt)*) => {{
+        let $name = $context;
+        $crate::cond! { $($arms)* }
+    }};
+    ($name:ident; $($arms:tt)*) => {
+        $crate::cond_with! { $name = &$name; $($arms)* }
+    };
+}